@@ -0,0 +1,142 @@
+use crate::{cli::ProcessConfig, config::Config};
+use std::fs::{self, File, OpenOptions};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tokio::process::Command as TokioCommand;
+
+const CGROUP_MOUNT: &str = "/sys/fs/cgroup";
+const DAEMON_LEAF: &str = "rpm-daemon";
+const CPU_PERIOD_US: u64 = 100_000;
+
+/// The cgroup that managed processes are created under, or `None` when cgroup
+/// placement is disabled or unavailable and limits are enforced by polling.
+static ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// Sets up cgroup placement if `use_cgroups` is enabled. Only the first call
+/// has an effect; if cgroup v2 can't be used, a warning is logged and limits
+/// fall back to the monitor's polling.
+pub fn configure(config: &Config) {
+    ROOT.get_or_init(|| {
+        if !config.use_cgroups {
+            return None;
+        }
+        match setup_root() {
+            Ok(root) => {
+                tracing::info!("Placing processes in cgroups under {}", root.display());
+                Some(root)
+            }
+            Err(e) => {
+                tracing::warn!("cgroup v2 unavailable, falling back to polled limits: {}", e);
+                None
+            }
+        }
+    });
+}
+
+/// cgroup v2 only delegates controllers from a cgroup that has no processes of
+/// its own, so the daemon first moves itself into a leaf next to the ones it
+/// creates for its processes.
+fn setup_root() -> std::io::Result<PathBuf> {
+    let mount = Path::new(CGROUP_MOUNT);
+    if !mount.join("cgroup.controllers").exists() {
+        return Err(std::io::Error::other(format!("no cgroup v2 hierarchy at {}", CGROUP_MOUNT)));
+    }
+
+    let membership = fs::read_to_string("/proc/self/cgroup")?;
+    let own = membership
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .ok_or_else(|| std::io::Error::other("daemon is not in a cgroup v2 hierarchy"))?;
+
+    let mut root = mount.join(own.trim_start_matches('/'));
+    // A daemon started from inside an earlier daemon's leaf reuses that root.
+    if root.file_name().is_some_and(|name| name == DAEMON_LEAF) {
+        root.pop();
+    }
+
+    let leaf = root.join(DAEMON_LEAF);
+    fs::create_dir_all(&leaf)?;
+    fs::write(leaf.join("cgroup.procs"), "0")?;
+    fs::write(root.join("cgroup.subtree_control"), "+memory +cpu")?;
+    Ok(root)
+}
+
+/// A cgroup holding a single managed process and carrying its limits.
+pub struct Cgroup {
+    path: PathBuf,
+    procs: File,
+}
+
+impl Cgroup {
+    /// Creates the process's cgroup with its memory and CPU limits applied.
+    /// Returns `None` when cgroups aren't in use or the cgroup can't be set up.
+    pub fn create(config: &ProcessConfig) -> Option<Self> {
+        let root = ROOT.get()?.as_ref()?;
+        let path = root.join(format!("rpm-{}", config.name.replace('/', "_")));
+
+        match Self::setup(&path, config) {
+            Ok(procs) => Some(Cgroup { path, procs }),
+            Err(e) => {
+                tracing::warn!("Failed to create cgroup for '{}': {}", config.name, e);
+                let _ = fs::remove_dir(&path);
+                None
+            }
+        }
+    }
+
+    fn setup(path: &Path, config: &ProcessConfig) -> std::io::Result<File> {
+        fs::create_dir_all(path)?;
+
+        let memory_max = match config.max_memory {
+            Some(mb) => (mb * 1024 * 1024).to_string(),
+            None => "max".to_string(),
+        };
+        fs::write(path.join("memory.max"), memory_max)?;
+
+        let cpu_max = match config.max_cpu {
+            Some(percent) => format!("{} {}", u64::from(percent) * CPU_PERIOD_US / 100, CPU_PERIOD_US),
+            None => "max".to_string(),
+        };
+        fs::write(path.join("cpu.max"), cpu_max)?;
+
+        OpenOptions::new().write(true).open(path.join("cgroup.procs"))
+    }
+
+    /// Makes the child join this cgroup between fork and exec, so it and
+    /// anything it spawns are accounted for from the first instruction.
+    pub fn prepare(&self, cmd: &mut TokioCommand) {
+        let fd = self.procs.as_raw_fd();
+        unsafe {
+            cmd.pre_exec(move || {
+                // Writing "0" moves the writing process; only a raw write is
+                // safe to call here.
+                if libc::write(fd, b"0".as_ptr() as *const libc::c_void, 1) < 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    }
+
+    /// Memory charged to the cgroup, in bytes.
+    pub fn memory_current(&self) -> Option<u64> {
+        fs::read_to_string(self.path.join("memory.current"))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    }
+
+    /// Sends SIGKILL to every process in the cgroup. Needs Linux 5.14 or later.
+    pub fn kill(&self) -> std::io::Result<()> {
+        fs::write(self.path.join("cgroup.kill"), "1")
+    }
+
+    /// Removes the cgroup. It must be empty, so call this after the process exits.
+    pub fn remove(self) {
+        if let Err(e) = fs::remove_dir(&self.path) {
+            tracing::debug!("Failed to remove cgroup {}: {}", self.path.display(), e);
+        }
+    }
+}
@@ -1,5 +1,6 @@
-use clap::{Parser, Subcommand};
-use serde::{Deserialize, Serialize};
+use clap::{Args, Parser, Subcommand};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Parser)]
 #[command(name = "rpm")]
@@ -8,44 +9,62 @@ use serde::{Deserialize, Serialize};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+    #[arg(long, global = true, help = "Show timestamps in local time instead of UTC")]
+    pub local: bool,
+    #[arg(short, long, global = true, help = "Only print errors, warnings and the requested data")]
+    pub quiet: bool,
+    #[arg(short, long, global = true, action = clap::ArgAction::Count, help = "Debug output (-v), or trace output (-vv), for the CLI and a daemon started with it")]
+    pub verbose: u8,
+    #[arg(long, global = true, value_enum, help = "Show CPU as percent of one core (per-core) or of all cores (whole-machine); defaults to cpu_mode in config.json")]
+    pub cpu_mode: Option<crate::ui::CpuMode>,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     #[command(about = "Start a new process")]
-    Start {
-        #[arg(help = "Command to execute")]
-        command: String,
-        #[arg(short, long, help = "Process name")]
-        name: Option<String>,
-        #[arg(short, long, help = "Working directory")]
-        cwd: Option<String>,
-        #[arg(short = 'i', long, help = "Number of instances to start", default_value = "1")]
-        instances: u32,
-        #[arg(long, help = "Auto restart on failure", default_value = "true")]
-        autorestart: bool,
-        #[arg(long, help = "Max memory usage (MB)")]
-        max_memory: Option<u64>,
-        #[arg(long, help = "Environment variables (key=value)")]
-        env: Vec<String>,
-    },
-    #[command(about = "Stop a process")]
+    Start(Box<StartArgs>),
+    #[command(about = "Run a command in the foreground as RPM would, without the daemon or saving it")]
+    Try(Box<StartArgs>),
+    #[command(about = "Stop one or more processes")]
     Stop {
-        #[arg(help = "Process name or ID")]
-        name: String,
+        #[arg(help = "Process names or IDs, `all`, or wildcard patterns like 'api-*'", required = true)]
+        names: Vec<String>,
+        #[arg(long, help = "Seconds to wait for a graceful exit before killing (overrides kill_timeout)")]
+        timeout: Option<u64>,
     },
-    #[command(about = "Restart a process")]
+    #[command(about = "Restart one or more processes")]
     Restart {
-        #[arg(help = "Process name or ID")]
-        name: String,
+        #[arg(help = "Process names or IDs, `all`, or wildcard patterns like 'api-*'", required_unless_present = "group")]
+        names: Vec<String>,
+        #[arg(long, help = "Re-read the process env from its ecosystem file before restarting")]
+        update_env: bool,
+        #[arg(long, help = "Seconds to wait for a graceful exit before killing (overrides kill_timeout)")]
+        timeout: Option<u64>,
+        #[arg(long, conflicts_with = "names", help = "Restart every process in this group")]
+        group: Option<String>,
+        #[arg(long, requires = "group", conflicts_with = "names", help = "Restart the group one process at a time so it never goes fully down")]
+        rolling: bool,
+        #[arg(long, default_value_t = 5, requires = "rolling", help = "Seconds each process must stay up before the next one restarts")]
+        rolling_wait: u64,
     },
-    #[command(about = "Delete a process")]
+    #[command(about = "Delete one or more processes")]
     Delete {
-        #[arg(help = "Process name or ID")]
-        name: String,
+        #[arg(help = "Process names or IDs, `all`, or wildcard patterns like 'api-*'", required = true)]
+        names: Vec<String>,
+        #[arg(short, long, help = "Skip the confirmation prompt")]
+        yes: bool,
     },
     #[command(about = "List all processes")]
-    List,
+    List {
+        #[arg(long, value_enum, help = "Sort the list by this column")]
+        sort: Option<crate::ui::SortKey>,
+        #[arg(long, help = "Add a column with each process's description")]
+        show_desc: bool,
+        #[arg(long, conflicts_with = "show_desc", help = "Print `json`, or one line per process from a template like '{{.name}}\t{{.pid}}'")]
+        format: Option<String>,
+    },
+    #[command(about = "Print a one-shot resource snapshot sorted by CPU")]
+    Top,
     #[command(about = "Show process logs")]
     Logs {
         #[arg(help = "Process name or ID")]
@@ -54,11 +73,28 @@ pub enum Commands {
         lines: usize,
         #[arg(short, long, help = "Follow log output")]
         follow: bool,
+        #[arg(long, help = "Print each line as a JSON object with ts, stream and msg")]
+        json: bool,
+    },
+    #[command(about = "Stream crash and resource warning events from the daemon as they happen")]
+    Events {
+        #[arg(long, help = "Print each event as a JSON object on its own line")]
+        json: bool,
+    },
+    #[command(about = "Show the daemon's own log (needs daemon_log_sink set to file)")]
+    DaemonLogs {
+        #[arg(short, long, help = "Number of lines to show", default_value = "50")]
+        lines: usize,
+        #[arg(short, long, help = "Follow log output")]
+        follow: bool,
     },
     #[command(about = "Show detailed process information")]
+    #[command(visible_alias = "describe")]
     Show {
         #[arg(help = "Process name or ID")]
         name: String,
+        #[arg(long, help = "Print the process as a JSON object")]
+        json: bool,
     },
     #[command(about = "Monitor processes in real-time")]
     Monitor,
@@ -66,11 +102,91 @@ pub enum Commands {
     Daemon {
         #[arg(long, help = "Run daemon in foreground")]
         foreground: bool,
+        #[arg(long, value_enum, help = "Where the daemon writes its own logs (overrides config)")]
+        log_sink: Option<crate::logging::LogSink>,
+        #[arg(long, value_enum, help = "Daemon log format (defaults to $RPM_LOG_FORMAT, then text)")]
+        log_format: Option<crate::logging::LogFormat>,
+    },
+    #[command(about = "Install the daemon as a service that starts on boot")]
+    Startup {
+        #[arg(long, help = "Install a per-user service (systemd user unit or launchd agent) instead of a system one")]
+        user: bool,
+        #[arg(long, value_enum, default_value = "always", help = "systemd Restart= policy for the daemon")]
+        restart: crate::daemon::RestartPolicy,
+        #[arg(long, default_value_t = 5, help = "systemd RestartSec=, in seconds")]
+        restart_sec: u64,
+        #[arg(long, help = "Directory for the daemon's stdout/stderr files (launchd only)")]
+        log_dir: Option<std::path::PathBuf>,
+        #[arg(long, help = "Print the unit file, plist or sc command instead of installing anything")]
+        dry_run: bool,
     },
     #[command(about = "Stop the daemon")]
-    Kill,
-    #[command(about = "Reload process configuration")]
+    Kill {
+        #[arg(short, long, help = "Skip the confirmation prompt")]
+        yes: bool,
+    },
+    #[command(about = "Kill processes and their children immediately with SIGKILL, skipping the graceful stop")]
+    KillProcess {
+        #[arg(help = "Process names or IDs, `all`, or wildcard patterns like 'api-*'", required = true)]
+        names: Vec<String>,
+        #[arg(short, long, help = "Skip the confirmation prompt")]
+        yes: bool,
+    },
+    #[command(about = "Change a process's environment and restart it with the new one")]
+    Update {
+        #[arg(help = "Process name or ID")]
+        name: String,
+        #[arg(long, value_name = "KEY=VALUE", help = "Environment variables to set (repeatable)")]
+        env: Vec<String>,
+        #[arg(long, conflicts_with = "replace_env", help = "Merge --env into the current env, overriding variables already set (default)")]
+        append_env: bool,
+        #[arg(long, help = "Discard the current env and use only --env")]
+        replace_env: bool,
+    },
+    #[command(about = "Reload a process from its definition on disk, or apply an ecosystem file to the running set")]
     Reload {
+        #[arg(help = "Process name or ID, or an ecosystem JSON file")]
+        name: String,
+    },
+    #[command(about = "Start a process automatically on daemon boot and resurrect")]
+    Enable {
+        #[arg(help = "Process name or ID")]
+        name: String,
+    },
+    #[command(about = "Keep a process defined but don't start it on daemon boot and resurrect")]
+    Disable {
+        #[arg(help = "Process name or ID")]
+        name: String,
+    },
+    #[command(about = "Stop auto-restarting and memory-guarding a process, e.g. while debugging it, without stopping it")]
+    Pause {
+        #[arg(help = "Process name or ID")]
+        name: String,
+    },
+    #[command(about = "Resume auto-restarts and memory guarding after `rpm pause`")]
+    Resume {
+        #[arg(help = "Process name or ID")]
+        name: String,
+    },
+    #[command(about = "Write a line to a process's stdin (needs --stdin at start)")]
+    Send {
+        #[arg(help = "Process name or ID")]
+        name: String,
+        #[arg(help = "Text to send; a newline is appended")]
+        text: String,
+    },
+    #[command(about = "Reopen all log files, e.g. after an external logrotate")]
+    ReloadLogs {
+        #[arg(long, help = "Rotate every process's logs before reopening")]
+        rotate: bool,
+    },
+    #[command(about = "Change how often the daemon checks on processes, until it restarts")]
+    SetInterval {
+        #[arg(help = "Seconds between monitor checks")]
+        secs: u64,
+    },
+    #[command(about = "Stream a process's output live, forwarding stdin if it has a pipe")]
+    Attach {
         #[arg(help = "Process name or ID")]
         name: String,
     },
@@ -78,59 +194,595 @@ pub enum Commands {
     Save,
     #[command(about = "Resurrect saved processes")]
     Resurrect,
-    #[command(about = "Show daemon status")]
-    Status,
+    #[command(about = "Show daemon status; exits non-zero when processes are unhealthy")]
+    Status {
+        #[arg(long, value_enum, default_value = "errored", help = "Which processes count as unhealthy")]
+        fail_on: crate::process::FailOn,
+    },
+    #[command(about = "Exit 0 if a process is running and non-zero otherwise, printing nothing")]
+    IsRunning {
+        #[arg(help = "Process name or ID")]
+        name: String,
+    },
+    #[command(about = "Show CLI and daemon versions and the paths in use")]
+    Info,
+    #[command(about = "Print a roff man page for rpm to stdout")]
+    Man,
+}
+
+#[derive(Args)]
+#[command(group(clap::ArgGroup::new("program").required(true).args(["command", "argv"])))]
+pub struct StartArgs {
+    #[arg(help = "Command to execute, or a JSON ecosystem file of process definitions")]
+    pub command: Option<String>,
+    #[arg(long, value_name = "JSON", help = "Program and arguments as a JSON array, run directly without a shell (e.g. '[\"node\",\"server.js\"]')")]
+    pub argv: Option<String>,
+    #[arg(short, long, help = "Process name")]
+    pub name: Option<String>,
+    #[arg(long, help = "Group to restart together with `rpm restart --group`")]
+    pub group: Option<String>,
+    #[arg(long = "desc", help = "Free-form description shown in `rpm show` and `rpm list --show-desc`")]
+    pub comment: Option<String>,
+    #[arg(long = "meta", value_name = "KEY=VALUE", help = "Metadata such as a git sha or owner, shown by `rpm show` and sent with crash events")]
+    pub metadata: Vec<String>,
+    #[arg(short, long, help = "Working directory")]
+    pub cwd: Option<String>,
+    #[arg(short = 'i', long, help = "Number of instances to start", default_value = "1")]
+    pub instances: u32,
+    #[arg(long, help = "Auto restart on failure", default_value = "true")]
+    pub autorestart: bool,
+    #[arg(long, help = "Max memory usage (MB)")]
+    pub max_memory: Option<u64>,
+    #[arg(long, help = "Memory usage (MB) above which a warning event is sent, without restarting")]
+    pub warn_memory: Option<u64>,
+    #[arg(long, help = "CPU usage (% of one core) above which a warning event is sent, without restarting")]
+    pub warn_cpu: Option<u32>,
+    #[arg(long, help = "Max CPU usage (% of one core, enforced with cgroups only)")]
+    pub max_cpu: Option<u32>,
+    #[arg(long, help = "Environment variables (key=value)")]
+    pub env: Vec<String>,
+    #[arg(long, help = "Merge stderr into stdout as a single log stream")]
+    pub merge_logs: bool,
+    #[arg(long, value_name = "PATH", help = "Write stdout to this file instead of the logs directory")]
+    pub out_file: Option<String>,
+    #[arg(long, value_name = "PATH", conflicts_with = "merge_logs", help = "Write stderr to this file instead of the logs directory")]
+    pub err_file: Option<String>,
+    #[arg(long, help = "Restart the process when files under this path change")]
+    pub watch: Vec<String>,
+    #[arg(long, help = "Path patterns that never trigger a watch restart", default_values = ["node_modules", ".git"])]
+    pub watch_ignore: Vec<String>,
+    #[arg(long, help = "Quiet period (ms) to wait for before a watch restart", default_value = "1000")]
+    pub watch_delay: u64,
+    #[arg(long, help = "Env profile to apply over the base env (e.g. production)")]
+    pub profile: Option<String>,
+    #[arg(long, help = "Run the program directly instead of through sh -c")]
+    pub no_shell: bool,
+    #[arg(long, help = "Keep stdin open as a pipe so input can be sent with `rpm send`")]
+    pub stdin: bool,
+    #[arg(long, help = "Seconds to wait for a graceful exit on stop before killing", default_value = "5")]
+    pub kill_timeout: u64,
+    #[arg(long, help = "Seconds after each start during which health checks (memory limit, warning thresholds) are skipped", default_value = "3")]
+    pub warmup: u64,
+    #[arg(long, help = "Milliseconds to wait between stopping and starting on restart (default 500)")]
+    pub restart_delay: Option<u64>,
+    #[arg(long, help = "Add up to this fraction (0-1) of the restart delay at random, to spread out restarts")]
+    pub restart_jitter: Option<f64>,
+    #[arg(long, help = "Double the restart delay for each consecutive crash restart, up to 30s")]
+    pub backoff: bool,
+    #[arg(long, help = "Crash restarts allowed within restart_window before giving up (overrides max_restarts_in_window)")]
+    pub max_restarts: Option<u32>,
+    #[arg(long, help = "Seconds a run must last to count as stable, clearing the crash restart history")]
+    pub min_uptime: Option<u64>,
+    #[arg(long, help = "TCP port the process listens on; restarts wait for it to be free before starting again")]
+    pub ready_port: Option<u16>,
+    #[arg(long, help = "Only pass this daemon env variable on to the process, clearing the rest (repeatable)")]
+    pub passthrough_env: Vec<String>,
+    #[arg(long, help = "Pass on daemon env variables starting with this prefix, clearing the rest (repeatable)")]
+    pub env_prefix: Vec<String>,
+    #[arg(long, help = "Max log lines per second per output stream; extra lines are dropped")]
+    pub log_rate_limit: Option<u32>,
+    #[arg(long, help = "Skip the check for the same command already running under another name")]
+    pub force: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessConfig {
     pub name: String,
+    #[serde(default)]
+    pub group: Option<String>,
     pub command: String,
+    /// Run the command through `sh -c`. Windows always runs the program directly.
+    #[serde(default = "default_shell")]
+    pub shell: bool,
+    /// Program and arguments from `--argv`, run as given instead of `command`,
+    /// which then only holds them quoted for display.
+    #[serde(default)]
+    pub args: Option<Vec<String>>,
     pub cwd: Option<String>,
+    #[serde(default = "default_instances")]
     pub instances: u32,
+    #[serde(default = "default_autorestart")]
     pub autorestart: bool,
     pub max_memory: Option<u64>,
+    /// Soft thresholds: crossing one sends a `ThresholdEvent` and flags the
+    /// process, but never restarts it.
+    #[serde(default)]
+    pub warn_memory: Option<u64>,
+    #[serde(default)]
+    pub warn_cpu: Option<u32>,
+    /// CPU quota in percent of one core. Only enforced when cgroups are in use.
+    #[serde(default)]
+    pub max_cpu: Option<u32>,
+    /// Variables forwarded from the daemon's environment. When non-empty the
+    /// rest of the daemon's environment is cleared; empty inherits all of it.
+    #[serde(default)]
+    pub passthrough_env: Vec<String>,
+    /// Daemon variables whose names start with one of these are forwarded
+    /// too, with the rest cleared as for `passthrough_env`.
+    #[serde(default)]
+    pub env_prefix: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_env")]
     pub env: Vec<(String, String)>,
+    /// Named env sets (like PM2's `env_production`) merged over `env` when selected.
+    #[serde(default, deserialize_with = "deserialize_env_profiles")]
+    pub env_profiles: BTreeMap<String, Vec<(String, String)>>,
+    /// Profile from `env_profiles` applied at start.
+    #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub merge_logs: bool,
+    /// Where stdout is logged instead of `<name>-out.log` in the logs directory.
+    #[serde(default)]
+    pub out_file: Option<String>,
+    /// Where stderr is logged instead of `<name>-err.log`. Unused with `merge_logs`.
+    #[serde(default)]
+    pub err_file: Option<String>,
+    /// Lines per second recorded from each output stream; unset means unlimited.
+    #[serde(default)]
+    pub log_rate_limit: Option<u32>,
+    /// Give the process a stdin pipe instead of `/dev/null`.
+    #[serde(default)]
+    pub stdin: bool,
+    #[serde(default)]
+    pub watch: Vec<String>,
+    #[serde(default = "default_watch_ignore")]
+    pub watch_ignore: Vec<String>,
+    #[serde(default = "default_watch_delay")]
+    pub watch_delay: u64,
+    /// Seconds between SIGTERM and SIGKILL when stopping.
+    #[serde(default = "default_kill_timeout")]
+    pub kill_timeout: u64,
+    /// Seconds after each start before health checks apply, so a slow boot
+    /// isn't restarted for a memory spike or flagged over a threshold.
+    #[serde(default = "default_warmup")]
+    pub warmup: u64,
+    /// Milliseconds between stopping and starting again on restart; unset
+    /// means `DEFAULT_RESTART_DELAY_MS`.
+    #[serde(default)]
+    pub restart_delay: Option<u64>,
+    /// Random extra share (0 to 1) of `restart_delay` added per restart.
+    #[serde(default)]
+    pub restart_jitter: Option<f64>,
+    /// Double `restart_delay` for each consecutive crash restart, up to
+    /// `MAX_BACKOFF_MS`.
+    #[serde(default)]
+    pub backoff: bool,
+    /// Overrides the config's `max_restarts_in_window` for this process.
+    #[serde(default)]
+    pub max_restarts: Option<u32>,
+    /// Seconds after which a run counts as stable: when it ends, earlier
+    /// crash restarts no longer count toward `max_restarts` or `backoff`.
+    #[serde(default)]
+    pub min_uptime: Option<u64>,
+    /// TCP port the process listens on. A restart waits, up to
+    /// `PORT_FREE_TIMEOUT`, for the old run to release it.
+    #[serde(default)]
+    pub ready_port: Option<u16>,
+    /// Whether the process is started on daemon boot and resurrect.
+    #[serde(default = "default_autostart")]
+    pub autostart: bool,
+    /// Free-form note from the ecosystem file or `--desc`, kept with the
+    /// saved definition and shown by `rpm show`.
+    #[serde(default, alias = "description")]
+    pub comment: Option<String>,
+    /// Free-form annotations for automation, e.g. the deployed git sha.
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    /// Ecosystem file this definition was loaded from, if any.
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// Pause between stop and start on restart for processes without a `restart_delay`.
+/// There is no daemon-wide setting for it.
+pub const DEFAULT_RESTART_DELAY_MS: u64 = 500;
+
+/// Longest restart delay, whether set directly or reached through `backoff`.
+pub const MAX_BACKOFF_MS: u64 = 30_000;
+
+fn default_shell() -> bool {
+    true
+}
+
+fn default_instances() -> u32 {
+    1
+}
+
+fn default_autorestart() -> bool {
+    true
+}
+
+fn default_autostart() -> bool {
+    true
+}
+
+fn default_watch_ignore() -> Vec<String> {
+    vec!["node_modules".to_string(), ".git".to_string()]
+}
+
+fn default_kill_timeout() -> u64 {
+    5
+}
+
+fn default_warmup() -> u64 {
+    3
+}
+
+fn default_watch_delay() -> u64 {
+    1000
+}
+
+/// Env is accepted either as a list of `[key, value]` pairs (the saved format)
+/// or as a `{ "KEY": "value" }` object, which is friendlier in ecosystem files.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EnvFormat {
+    Pairs(Vec<(String, String)>),
+    Map(BTreeMap<String, String>),
+}
+
+impl From<EnvFormat> for Vec<(String, String)> {
+    fn from(format: EnvFormat) -> Self {
+        match format {
+            EnvFormat::Pairs(pairs) => pairs,
+            EnvFormat::Map(map) => map.into_iter().collect(),
+        }
+    }
+}
+
+fn deserialize_env<'de, D>(deserializer: D) -> Result<Vec<(String, String)>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(EnvFormat::deserialize(deserializer)?.into())
+}
+
+type EnvProfiles = BTreeMap<String, Vec<(String, String)>>;
+
+fn deserialize_env_profiles<'de, D>(deserializer: D) -> Result<EnvProfiles, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let profiles = BTreeMap::<String, EnvFormat>::deserialize(deserializer)?;
+    Ok(profiles.into_iter().map(|(name, env)| (name, env.into())).collect())
+}
+
+/// Checks that a process name is safe to use as a file name and map key:
+/// ASCII letters, digits, `-`, `_` and `.`, not starting with a `.`.
+pub fn validate_name(name: &str) -> crate::Result<()> {
+    if name.is_empty() {
+        return Err(crate::RpmError::Config("Process name can't be empty".to_string()));
+    }
+    if name.starts_with('.') {
+        return Err(crate::RpmError::Config(format!(
+            "Invalid process name '{}': names can't start with '.'",
+            name
+        )));
+    }
+    if let Some(c) = name.chars().find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))) {
+        return Err(crate::RpmError::Config(format!(
+            "Invalid process name '{}': {:?} is not allowed (use letters, digits, '-', '_' and '.')",
+            name.escape_default(),
+            c
+        )));
+    }
+    Ok(())
+}
+
+/// Derives a name from the program in `command`, e.g. `./bin/server --port 80`
+/// becomes `server`, replacing characters `validate_name` rejects with `-`.
+fn default_name(command: &str) -> String {
+    let program = command.split_whitespace().next().unwrap_or("");
+    let base = program.rsplit(['/', '\\']).next().unwrap_or(program);
+    let name: String = base
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '-' })
+        .collect();
+    let name = name.trim_start_matches('.');
+    if name.is_empty() {
+        "unknown".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Parses `KEY=VALUE` arguments as given to `--env`.
+pub fn parse_env(entries: Vec<String>) -> crate::Result<Vec<(String, String)>> {
+    entries
+        .into_iter()
+        .map(|e| {
+            let parts: Vec<&str> = e.splitn(2, '=').collect();
+            if parts.len() == 2 {
+                Ok((parts[0].to_string(), parts[1].to_string()))
+            } else {
+                Err(crate::RpmError::Config(format!("Invalid env format: {}", e)))
+            }
+        })
+        .collect()
 }
 
 impl ProcessConfig {
-    pub fn from_args(
-        command: String,
-        name: Option<String>,
-        cwd: Option<String>,
-        instances: u32,
-        autorestart: bool,
-        max_memory: Option<u64>,
-        env: Vec<String>,
-    ) -> crate::Result<Self> {
-        let name = name.unwrap_or_else(|| {
-            command
-                .split_whitespace()
-                .next()
-                .unwrap_or("unknown")
-                .to_string()
-        });
-
-        let env_vars: Result<Vec<(String, String)>, _> = env
-            .into_iter()
-            .map(|e| {
-                let parts: Vec<&str> = e.splitn(2, '=').collect();
-                if parts.len() == 2 {
-                    Ok((parts[0].to_string(), parts[1].to_string()))
-                } else {
-                    Err(crate::RpmError::Config(format!("Invalid env format: {}", e)))
+    /// A definition running `command` through the shell with every other
+    /// option at its default, for building definitions in code.
+    pub fn new(name: impl Into<String>, command: impl Into<String>) -> Self {
+        ProcessConfig {
+            name: name.into(),
+            group: None,
+            command: command.into(),
+            shell: default_shell(),
+            args: None,
+            cwd: None,
+            instances: default_instances(),
+            autorestart: default_autorestart(),
+            max_memory: None,
+            warn_memory: None,
+            warn_cpu: None,
+            max_cpu: None,
+            passthrough_env: Vec::new(),
+            env_prefix: Vec::new(),
+            env: Vec::new(),
+            env_profiles: BTreeMap::new(),
+            profile: None,
+            merge_logs: false,
+            out_file: None,
+            err_file: None,
+            log_rate_limit: None,
+            stdin: false,
+            watch: Vec::new(),
+            watch_ignore: default_watch_ignore(),
+            watch_delay: default_watch_delay(),
+            kill_timeout: default_kill_timeout(),
+            warmup: default_warmup(),
+            restart_delay: None,
+            restart_jitter: None,
+            backoff: false,
+            max_restarts: None,
+            min_uptime: None,
+            ready_port: None,
+            autostart: default_autostart(),
+            comment: None,
+            metadata: BTreeMap::new(),
+            source: None,
+        }
+    }
+
+    pub fn from_args(args: StartArgs) -> crate::Result<Self> {
+        let StartArgs {
+            command,
+            argv,
+            name,
+            group,
+            comment,
+            metadata,
+            cwd,
+            instances,
+            autorestart,
+            max_memory,
+            warn_memory,
+            warn_cpu,
+            max_cpu,
+            env,
+            merge_logs,
+            out_file,
+            err_file,
+            log_rate_limit,
+            passthrough_env,
+            env_prefix,
+            watch,
+            watch_ignore,
+            watch_delay,
+            profile,
+            no_shell,
+            stdin,
+            kill_timeout,
+            warmup,
+            restart_delay,
+            restart_jitter,
+            backoff,
+            max_restarts,
+            min_uptime,
+            ready_port,
+            force: _,
+        } = args;
+
+        let args = argv
+            .map(|json| {
+                let args: Vec<String> = serde_json::from_str(&json).map_err(|e| {
+                    crate::RpmError::Config(format!(
+                        "Invalid --argv '{}': expected a JSON array of strings such as [\"node\",\"server.js\"] ({})",
+                        json, e
+                    ))
+                })?;
+                if args.first().is_none_or(|program| program.is_empty()) {
+                    return Err(crate::RpmError::Config(
+                        "--argv needs at least the program to run".to_string(),
+                    ));
                 }
+                Ok(args)
+            })
+            .transpose()?;
+        let command = match &args {
+            Some(args) => args.iter().map(|arg| crate::ui::shell_quote(arg)).collect::<Vec<_>>().join(" "),
+            None => command.unwrap_or_default(),
+        };
+
+        let name = match name {
+            Some(name) => {
+                validate_name(&name)?;
+                name
+            }
+            None => default_name(&command),
+        };
+
+        if instances == 0 {
+            return Err(crate::RpmError::Config(format!(
+                "Process '{}' needs at least 1 instance",
+                name
+            )));
+        }
+
+        if restart_jitter.is_some_and(|jitter| !(0.0..=1.0).contains(&jitter)) {
+            return Err(crate::RpmError::Config(
+                "--restart-jitter must be between 0 and 1".to_string(),
+            ));
+        }
+
+        if max_restarts == Some(0) {
+            return Err(crate::RpmError::Config(
+                "--max-restarts must be at least 1 (use --autorestart false to never restart)".to_string(),
+            ));
+        }
+
+        if min_uptime == Some(0) {
+            return Err(crate::RpmError::Config(
+                "--min-uptime must be at least 1 second".to_string(),
+            ));
+        }
+
+        if ready_port == Some(0) {
+            return Err(crate::RpmError::Config(
+                "--ready-port must be between 1 and 65535".to_string(),
+            ));
+        }
+
+        if restart_delay.is_some_and(|delay| delay > MAX_BACKOFF_MS) {
+            return Err(crate::RpmError::Config(format!(
+                "--restart-delay must be at most {}ms",
+                MAX_BACKOFF_MS
+            )));
+        }
+
+        if env_prefix.iter().any(|prefix| prefix.is_empty()) {
+            return Err(crate::RpmError::Config(
+                "--env-prefix can't be empty".to_string(),
+            ));
+        }
+
+        if log_rate_limit == Some(0) {
+            return Err(crate::RpmError::Config(
+                "--log-rate-limit must be at least 1 line per second".to_string(),
+            ));
+        }
+
+        // The daemon runs elsewhere, so relative paths are resolved here.
+        let log_file = |path: Option<String>, flag: &str| -> crate::Result<Option<String>> {
+            let Some(path) = path else { return Ok(None) };
+            let path = std::env::current_dir()?.join(path);
+            if !path.parent().is_some_and(std::path::Path::is_dir) {
+                return Err(crate::RpmError::Config(format!(
+                    "{} '{}' is not in an existing directory",
+                    flag,
+                    path.display()
+                )));
+            }
+            Ok(Some(path.display().to_string()))
+        };
+        let out_file = log_file(out_file, "--out-file")?;
+        let err_file = log_file(err_file, "--err-file")?;
+
+        let metadata = metadata
+            .into_iter()
+            .map(|entry| match entry.split_once('=') {
+                Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+                _ => Err(crate::RpmError::Config(format!("Invalid --meta format: {}", entry))),
             })
-            .collect();
+            .collect::<crate::Result<BTreeMap<String, String>>>()?;
 
         Ok(ProcessConfig {
             name,
+            group,
             command,
+            shell: !no_shell && args.is_none(),
+            args,
             cwd,
             instances,
             autorestart,
             max_memory,
-            env: env_vars?,
+            warn_memory,
+            warn_cpu,
+            max_cpu,
+            passthrough_env,
+            env_prefix,
+            env: parse_env(env)?,
+            env_profiles: BTreeMap::new(),
+            profile,
+            merge_logs,
+            out_file,
+            err_file,
+            log_rate_limit,
+            stdin,
+            watch,
+            watch_ignore,
+            watch_delay,
+            kill_timeout,
+            warmup,
+            restart_delay,
+            restart_jitter,
+            backoff,
+            max_restarts,
+            min_uptime,
+            ready_port,
+            autostart: true,
+            comment,
+            metadata,
+            source: None,
         })
     }
+
+    /// Sets each variable in `vars`, replacing the value of one already in
+    /// `env` in place and appending the rest in order.
+    pub fn merge_env(&mut self, vars: Vec<(String, String)>) {
+        for (key, value) in vars {
+            match self.env.iter_mut().find(|(existing, _)| *existing == key) {
+                Some(entry) => entry.1 = value,
+                None => self.env.push((key, value)),
+            }
+        }
+    }
+
+    /// How long a restart waits between stopping and starting the process.
+    pub fn restart_delay(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.restart_delay.unwrap_or(DEFAULT_RESTART_DELAY_MS))
+    }
+
+    /// The base env with the selected profile's variables merged over it.
+    pub fn resolved_env(&self) -> crate::Result<Vec<(String, String)>> {
+        let mut env = self.env.clone();
+
+        if let Some(profile) = &self.profile {
+            let overrides = self.env_profiles.get(profile).ok_or_else(|| {
+                crate::RpmError::Config(format!(
+                    "Unknown env profile '{}' for process '{}'",
+                    profile, self.name
+                ))
+            })?;
+
+            for (key, value) in overrides {
+                env.retain(|(existing, _)| existing != key);
+                env.push((key.clone(), value.clone()));
+            }
+        }
+
+        Ok(env)
+    }
 }
\ No newline at end of file
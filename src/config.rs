@@ -1,28 +1,71 @@
-use crate::{process::ManagedProcess, Result, RpmError};
+use crate::{logging::LogSink, process::ManagedProcess, Result, RpmError};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::Value;
 use std::path::PathBuf;
 use tokio::fs;
 
+/// Current schema version of `config.json`. Files written before versioning
+/// was introduced have no `version` field and are treated as version 0.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Current schema version of `processes.json`. Version 0 is the original
+/// format: a bare array of process definitions.
+pub const PROCESSES_VERSION: u32 = 1;
+
+/// Any field missing from `config.json` takes its value from `Config::default()`,
+/// so hand-edited or partial files still load.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
+    pub version: u32,
     pub daemon_port: u16,
     pub max_processes: usize,
     pub log_max_size: u64,
     pub log_retention_days: u32,
-    pub auto_restart_delay: u64,
     pub health_check_interval: u64,
+    /// Seconds between CPU and memory samples of each process, for when
+    /// sampling every health check costs too much; unset samples every check.
+    pub sample_interval: Option<u64>,
+    /// Crash-loop detection: a process that needs more than
+    /// `max_restarts_in_window` crash restarts within `restart_window` seconds
+    /// is left errored instead of restarted again. 0 in either disables it.
+    pub restart_window: u64,
+    pub max_restarts_in_window: u32,
+    pub max_open_log_files: usize,
+    pub daemon_log_sink: LogSink,
+    /// IPC connections handled at once; further clients wait to be accepted.
+    pub max_ipc_connections: usize,
+    /// Place each process in its own cgroup v2 so the kernel enforces
+    /// `max_memory`/`max_cpu` (Linux only; falls back to polling otherwise).
+    pub use_cgroups: bool,
+    /// How the CLI displays CPU usage; `--cpu-mode` overrides it per command.
+    pub cpu_mode: crate::ui::CpuMode,
+    /// Port for the Prometheus `/metrics` endpoint; unset leaves it off.
+    pub metrics_port: Option<u16>,
+    /// IP address the metrics endpoint listens on. IPC is always local.
+    pub metrics_bind: String,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
+            version: CONFIG_VERSION,
             daemon_port: 9999,
             max_processes: 1000,
             log_max_size: 100 * 1024 * 1024, // 100MB
             log_retention_days: 30,
-            auto_restart_delay: 5,
             health_check_interval: 5,
+            sample_interval: None,
+            restart_window: 60,
+            max_restarts_in_window: 5,
+            max_open_log_files: 256,
+            daemon_log_sink: LogSink::default(),
+            max_ipc_connections: 64,
+            use_cgroups: false,
+            cpu_mode: crate::ui::CpuMode::default(),
+            metrics_port: None,
+            metrics_bind: "127.0.0.1".to_string(),
         }
     }
 }
@@ -36,9 +79,18 @@ impl Config {
                 RpmError::Config(format!("Failed to read config file: {}", e))
             })?;
             
-            serde_json::from_str(&content).map_err(|e| {
+            let mut value: Value = serde_json::from_str(&content).map_err(|e| {
                 RpmError::Config(format!("Failed to parse config file: {}", e))
-            })
+            })?;
+            let migrated = migrate_config(&mut value)?;
+
+            let config: Config = serde_json::from_value(value).map_err(|e| {
+                RpmError::Config(format!("Failed to parse config file: {}", e))
+            })?;
+            if migrated {
+                config.save().await?;
+            }
+            Ok(config)
         } else {
             let config = Config::default();
             config.save().await?;
@@ -64,7 +116,7 @@ impl Config {
         })
     }
 
-    pub async fn save_processes(&self, processes: &HashMap<String, ManagedProcess>) -> Result<()> {
+    pub async fn save_processes(&self, processes: &IndexMap<String, ManagedProcess>) -> Result<()> {
         let processes_path = get_processes_path()?;
         
         if let Some(parent) = processes_path.parent() {
@@ -75,10 +127,19 @@ impl Config {
 
         let process_configs: Vec<_> = processes
             .values()
-            .map(|p| &p.info.config)
+            .map(|p| SavedProcess {
+                id: Some(p.info.id),
+                restarts: p.info.restarts,
+                crashes: p.info.crashes,
+                config: &p.info.config,
+            })
             .collect();
+        let file = SavedProcesses {
+            version: PROCESSES_VERSION,
+            processes: process_configs,
+        };
 
-        let content = serde_json::to_string_pretty(&process_configs).map_err(|e| {
+        let content = serde_json::to_string_pretty(&file).map_err(|e| {
             RpmError::Config(format!("Failed to serialize processes: {}", e))
         })?;
 
@@ -87,32 +148,164 @@ impl Config {
         })
     }
 
-    pub async fn load_processes(&self) -> Result<HashMap<String, ManagedProcess>> {
+    /// Restores saved processes with their saved ids. Entries without an id
+    /// (written before ids were saved) or with one already taken get the
+    /// lowest free ids, in file order.
+    pub async fn load_processes(&self) -> Result<IndexMap<String, ManagedProcess>> {
+        let saved = self.load_saved_processes().await?;
+
+        let mut used = std::collections::HashSet::new();
+        let ids: Vec<Option<u32>> = saved
+            .iter()
+            .map(|entry| entry.id.filter(|id| used.insert(*id)))
+            .collect();
+
+        let mut free = (0..).filter(|id| !used.contains(id));
+        let mut processes = IndexMap::new();
+        for (entry, id) in saved.into_iter().zip(ids) {
+            let id = id.or_else(|| free.next()).unwrap_or_default();
+            let mut process = ManagedProcess::new(id, entry.config.clone());
+            process.info.restarts = entry.restarts;
+            process.info.crashes = entry.crashes;
+            processes.insert(entry.config.name, process);
+        }
+
+        Ok(processes)
+    }
+
+    pub async fn load_process_configs(&self) -> Result<Vec<crate::cli::ProcessConfig>> {
+        Ok(self
+            .load_saved_processes()
+            .await?
+            .into_iter()
+            .map(|entry| entry.config)
+            .collect())
+    }
+
+    async fn load_saved_processes(&self) -> Result<Vec<LoadedProcess>> {
         let processes_path = get_processes_path()?;
         
         if !processes_path.exists() {
-            return Ok(HashMap::new());
+            return Ok(Vec::new());
         }
 
         let content = fs::read_to_string(&processes_path).await.map_err(|e| {
             RpmError::Config(format!("Failed to read processes file: {}", e))
         })?;
 
-        let process_configs: Vec<crate::cli::ProcessConfig> = serde_json::from_str(&content)
-            .map_err(|e| {
-                RpmError::Config(format!("Failed to parse processes file: {}", e))
-            })?;
+        let parsed = serde_json::from_str(&content)
+            .map(migrate_processes)
+            .and_then(serde_json::from_value::<ProcessesFile>);
 
-        let mut processes = HashMap::new();
-        for config in process_configs {
-            let process = ManagedProcess::new(config.clone());
-            processes.insert(config.name.clone(), process);
+        match parsed {
+            Ok(file) => Ok(file.processes),
+            Err(e) => Err(quarantine_processes_file(&processes_path, e).await),
         }
+    }
+}
 
-        Ok(processes)
+#[derive(Serialize)]
+struct SavedProcesses<'a> {
+    version: u32,
+    processes: Vec<SavedProcess<'a>>,
+}
+
+#[derive(Serialize)]
+struct SavedProcess<'a> {
+    id: Option<u32>,
+    restarts: u32,
+    crashes: u32,
+    #[serde(flatten)]
+    config: &'a crate::cli::ProcessConfig,
+}
+
+#[derive(Deserialize)]
+struct ProcessesFile {
+    processes: Vec<LoadedProcess>,
+}
+
+#[derive(Deserialize)]
+struct LoadedProcess {
+    #[serde(default)]
+    id: Option<u32>,
+    #[serde(default)]
+    restarts: u32,
+    #[serde(default)]
+    crashes: u32,
+    #[serde(flatten)]
+    config: crate::cli::ProcessConfig,
+}
+
+/// Upgrades a parsed `config.json` to `CONFIG_VERSION` in place. Returns
+/// whether anything changed, so the caller can write the upgraded file back.
+fn migrate_config(value: &mut Value) -> Result<bool> {
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| RpmError::Config("Failed to parse config file: expected an object".to_string()))?;
+    let version = object.get("version").and_then(Value::as_u64).unwrap_or(0);
+
+    if version > u64::from(CONFIG_VERSION) {
+        tracing::warn!(
+            "Config file has schema version {}, newer than the supported {}; loading it anyway",
+            version,
+            CONFIG_VERSION
+        );
+        return Ok(false);
+    }
+    if version == u64::from(CONFIG_VERSION) {
+        return Ok(false);
+    }
+
+    // 0 -> 1: unversioned files may predate any of the optional settings;
+    // take the defaults for whatever is missing.
+    if version < 1 {
+        if let Value::Object(defaults) = serde_json::to_value(Config::default())? {
+            for (key, default) in defaults {
+                object.entry(key).or_insert(default);
+            }
+        }
+    }
+
+    object.insert("version".to_string(), Value::from(CONFIG_VERSION));
+    Ok(true)
+}
+
+/// Upgrades a parsed `processes.json` to the `PROCESSES_VERSION` layout.
+fn migrate_processes(value: Value) -> Value {
+    match value {
+        // 0 -> 1: the bare array moved under `processes` next to `version`.
+        Value::Array(processes) => serde_json::json!({
+            "version": PROCESSES_VERSION,
+            "processes": processes,
+        }),
+        other => other,
     }
 }
 
+/// Moves an unparseable processes file aside to `processes.json.bak` so the
+/// next save starts from a clean file instead of failing the same way, and
+/// returns the error describing what happened.
+async fn quarantine_processes_file(path: &std::path::Path, error: serde_json::Error) -> RpmError {
+    let backup = path.with_extension("json.bak");
+    let outcome = match fs::rename(path, &backup).await {
+        Ok(()) => format!("moved it to {}", backup.display()),
+        Err(e) => format!("failed to back it up: {}", e),
+    };
+
+    tracing::warn!(
+        "Processes file {} is corrupt ({}); {} and continuing with no saved processes",
+        path.display(),
+        error,
+        outcome
+    );
+    RpmError::Config(format!(
+        "Processes file {} is corrupt ({}); {}",
+        path.display(),
+        error,
+        outcome
+    ))
+}
+
 fn get_config_path() -> Result<PathBuf> {
     let project_dirs = directories::ProjectDirs::from("", "", "rpm")
         .ok_or_else(|| RpmError::Config("Failed to get project directories".to_string()))?;
@@ -127,6 +320,20 @@ fn get_processes_path() -> Result<PathBuf> {
     Ok(project_dirs.data_dir().join("processes.json"))
 }
 
+pub fn get_config_dir() -> Result<PathBuf> {
+    let project_dirs = directories::ProjectDirs::from("", "", "rpm")
+        .ok_or_else(|| RpmError::Config("Failed to get project directories".to_string()))?;
+
+    Ok(project_dirs.config_dir().to_path_buf())
+}
+
+pub fn get_data_dir() -> Result<PathBuf> {
+    let project_dirs = directories::ProjectDirs::from("", "", "rpm")
+        .ok_or_else(|| RpmError::Config("Failed to get project directories".to_string()))?;
+
+    Ok(project_dirs.data_dir().to_path_buf())
+}
+
 pub fn get_logs_dir() -> Result<PathBuf> {
     let project_dirs = directories::ProjectDirs::from("", "", "rpm")
         .ok_or_else(|| RpmError::Config("Failed to get project directories".to_string()))?;
@@ -139,17 +346,95 @@ pub fn get_logs_dir() -> Result<PathBuf> {
     Ok(logs_dir)
 }
 
-pub fn get_pids_dir() -> Result<PathBuf> {
+pub fn get_daemon_log_path() -> Result<PathBuf> {
     let project_dirs = directories::ProjectDirs::from("", "", "rpm")
         .ok_or_else(|| RpmError::Config("Failed to get project directories".to_string()))?;
-    
-    let pids_dir = project_dirs.runtime_dir()
-        .unwrap_or_else(|| project_dirs.data_dir())
-        .join("pids");
-    
+
+    let data_dir = project_dirs.data_dir();
+    std::fs::create_dir_all(data_dir).map_err(|e| {
+        RpmError::Config(format!("Failed to create data directory: {}", e))
+    })?;
+
+    Ok(data_dir.join("daemon.log"))
+}
+
+pub fn get_pids_dir() -> Result<PathBuf> {
+    let pids_dir = get_runtime_dir()?.join("pids");
+
     std::fs::create_dir_all(&pids_dir).map_err(|e| {
         RpmError::Config(format!("Failed to create pids directory: {}", e))
     })?;
-    
+
     Ok(pids_dir)
-}
\ No newline at end of file
+}
+
+/// Where the daemon records its PID while it runs.
+pub fn get_daemon_pid_path() -> Result<PathBuf> {
+    Ok(get_runtime_dir()?.join("daemon.pid"))
+}
+
+#[cfg(windows)]
+pub fn get_runtime_dir() -> Result<PathBuf> {
+    let project_dirs = directories::ProjectDirs::from("", "", "rpm")
+        .ok_or_else(|| RpmError::Config("Failed to get project directories".to_string()))?;
+
+    let dir = project_dirs.runtime_dir()
+        .unwrap_or_else(|| project_dirs.data_dir())
+        .to_path_buf();
+
+    std::fs::create_dir_all(&dir).map_err(|e| {
+        RpmError::Config(format!("Failed to create runtime directory {}: {}", dir.display(), e))
+    })?;
+
+    Ok(dir)
+}
+
+/// Directory for the daemon's socket and other runtime files, created if
+/// missing and restricted to the current user.
+#[cfg(unix)]
+pub fn get_runtime_dir() -> Result<PathBuf> {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    let uid = unsafe { libc::getuid() };
+    let xdg_runtime_dir = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+    let dir = resolve_runtime_dir(xdg_runtime_dir.as_deref(), uid, |path| path.is_dir());
+
+    std::fs::create_dir_all(&dir).map_err(|e| {
+        RpmError::Config(format!("Failed to create runtime directory {}: {}", dir.display(), e))
+    })?;
+    // The /tmp fallback is shared with other users, so don't trust a directory
+    // someone else created there.
+    let metadata = std::fs::metadata(&dir)?;
+    if metadata.uid() != uid {
+        return Err(RpmError::Config(format!(
+            "Runtime directory {} is owned by another user",
+            dir.display()
+        )));
+    }
+    std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700))?;
+
+    Ok(dir)
+}
+
+/// Picks the runtime directory, always on a local filesystem so a Unix socket
+/// can live there: `$XDG_RUNTIME_DIR/rpm` if set, then `/run/user/<uid>/rpm`
+/// if that directory exists, then `/tmp/rpm-<uid>`. Takes its inputs
+/// explicitly so the choice doesn't depend on the calling environment.
+#[cfg(unix)]
+pub fn resolve_runtime_dir(
+    xdg_runtime_dir: Option<&std::path::Path>,
+    uid: u32,
+    is_dir: impl Fn(&std::path::Path) -> bool,
+) -> PathBuf {
+    // The XDG spec says relative paths are invalid and must be ignored.
+    if let Some(dir) = xdg_runtime_dir.filter(|dir| dir.is_absolute()) {
+        return dir.join("rpm");
+    }
+
+    let run_user = PathBuf::from(format!("/run/user/{}", uid));
+    if is_dir(&run_user) {
+        return run_user.join("rpm");
+    }
+
+    PathBuf::from(format!("/tmp/rpm-{}", uid))
+}
@@ -1,5 +1,8 @@
 use crate::{Result, RpmError};
+use chrono::{DateTime, Utc};
 use std::process::Command;
+use std::sync::OnceLock;
+#[cfg(windows)]
 use tokio::time::Duration;
 
 #[cfg(windows)]
@@ -21,9 +24,73 @@ async fn start_daemon_foreground() -> Result<()> {
 }
 
 async fn start_daemon_background() -> Result<()> {
+    install_startup(&ServiceOptions::default()).await
+}
+
+/// When systemd restarts the daemon, mirroring the unit's `Restart=` values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum RestartPolicy {
+    No,
+    #[default]
+    Always,
+    OnSuccess,
+    OnFailure,
+    OnAbnormal,
+    OnAbort,
+    OnWatchdog,
+}
+
+impl RestartPolicy {
+    /// The keyword written to the unit file.
+    pub fn as_systemd(&self) -> &'static str {
+        match self {
+            RestartPolicy::No => "no",
+            RestartPolicy::Always => "always",
+            RestartPolicy::OnSuccess => "on-success",
+            RestartPolicy::OnFailure => "on-failure",
+            RestartPolicy::OnAbnormal => "on-abnormal",
+            RestartPolicy::OnAbort => "on-abort",
+            RestartPolicy::OnWatchdog => "on-watchdog",
+        }
+    }
+}
+
+/// How `rpm startup` registers the daemon with the system's service manager.
+#[derive(Debug, Clone)]
+pub struct ServiceOptions {
+    /// Install a per-user service (systemd user unit) that needs no root.
+    pub user: bool,
+    /// systemd `Restart=` policy.
+    pub restart: RestartPolicy,
+    /// systemd `RestartSec=`, in seconds.
+    pub restart_sec: u64,
+    /// Print what would be installed and run instead of doing it.
+    pub dry_run: bool,
+    /// Where launchd writes the daemon's stdout and stderr. Defaults to the
+    /// data directory for a user agent and `/tmp` for a system daemon.
+    pub log_dir: Option<std::path::PathBuf>,
+}
+
+impl Default for ServiceOptions {
+    fn default() -> Self {
+        Self {
+            user: false,
+            restart: RestartPolicy::Always,
+            restart_sec: 5,
+            log_dir: None,
+            dry_run: false,
+        }
+    }
+}
+
+/// Installs the daemon as a service that starts on boot, and starts it.
+pub async fn install_startup(options: &ServiceOptions) -> Result<()> {
     #[cfg(windows)]
     {
-        match windows_service::install_and_start_service().await {
+        if options.user {
+            return Err(RpmError::Daemon("--user is not supported on Windows".to_string()));
+        }
+        match windows_service::install_and_start_service(options).await {
             Ok(()) => Ok(()),
             Err(e) => {
                 eprintln!("Failed to install as Windows service: {}", e);
@@ -33,24 +100,90 @@ async fn start_daemon_background() -> Result<()> {
     }
     #[cfg(target_os = "macos")]
     {
-        macos_service::install_and_start_launchd_service().await
+        macos_service::install_and_start_launchd_service(options).await
     }
     #[cfg(target_os = "linux")]
     {
-        linux_service::install_and_start_systemd_service().await
+        linux_service::install_and_start_systemd_service(options).await
     }
     #[cfg(all(unix, not(target_os = "macos"), not(target_os = "linux")))]
     {
+        if options.user {
+            return Err(RpmError::Daemon("--user needs systemd and is only supported on Linux".to_string()));
+        }
+        if options.dry_run {
+            println!("Would fork the daemon into the background; nothing is installed on this platform.");
+            return Ok(());
+        }
         unix_daemon::daemonize_and_start().await
     }
     #[cfg(not(any(windows, unix)))]
     {
+        let _ = options;
         Err(RpmError::Daemon(
             "Background service mode is not supported on this platform. Please run in foreground mode with --foreground.".to_string()
         ))
     }
 }
 
+/// The binary a service should run: `rpm-daemon`, which accepts `--service`,
+/// when it sits next to the running executable.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn service_executable() -> Result<std::path::PathBuf> {
+    let current_exe = std::env::current_exe()
+        .map_err(|e| RpmError::Daemon(format!("Failed to get current exe: {}", e)))?;
+    let daemon_exe = current_exe.with_file_name("rpm-daemon");
+    Ok(if daemon_exe.is_file() { daemon_exe } else { current_exe })
+}
+
+/// Prints the file a service install would write and the commands it would run.
+#[cfg(any(target_os = "linux", target_os = "macos", windows))]
+fn print_dry_run(file: Option<(&std::path::Path, &str)>, commands: &[Vec<String>]) {
+    if let Some((path, content)) = file {
+        println!("Would write {}:", path.display());
+        println!();
+        println!("{}", content.trim_end());
+        println!();
+    }
+    println!("Would run:");
+    for command in commands {
+        println!("  {}", command.join(" "));
+    }
+}
+
+/// Resolves on Ctrl+C, or on Unix also SIGTERM, which is how service
+/// managers stop the daemon.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = term.recv() => {}
+                }
+                return;
+            }
+            Err(e) => tracing::warn!("Failed to install SIGTERM handler: {}", e),
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+static STARTED_AT: OnceLock<DateTime<Utc>> = OnceLock::new();
+static SHUTDOWN: tokio::sync::Notify = tokio::sync::Notify::const_new();
+
+/// When this process started running as the daemon, if it is one.
+pub fn started_at() -> Option<DateTime<Utc>> {
+    STARTED_AT.get().copied()
+}
+
+/// Makes a running daemon shut down as it would on a signal.
+pub fn request_shutdown() {
+    SHUTDOWN.notify_one();
+}
+
 pub struct DaemonManager {
     process_manager: crate::process::ProcessManager,
     ipc_server: crate::ipc::IpcServer,
@@ -59,7 +192,8 @@ pub struct DaemonManager {
 impl DaemonManager {
     pub async fn new() -> Result<Self> {
         let process_manager = crate::process::ProcessManager::new().await?;
-        let ipc_server = crate::ipc::IpcServer::new().await?;
+        let ipc_server =
+            crate::ipc::IpcServer::new(process_manager.config().max_ipc_connections).await?;
         
         Ok(DaemonManager {
             process_manager,
@@ -69,21 +203,51 @@ impl DaemonManager {
 
     pub async fn run(self) -> Result<()> {
         tracing::info!("RPM daemon started");
+        STARTED_AT.get_or_init(Utc::now);
+        // `new` has already made sure no other daemon is running, so any
+        // existing PID file is stale.
+        let pid_path = crate::config::get_daemon_pid_path()?;
+        std::fs::write(&pid_path, format!("{}\n", std::process::id()))
+            .map_err(|e| RpmError::Daemon(format!("Failed to write {}: {}", pid_path.display(), e)))?;
         
-        let process_manager = std::sync::Arc::new(tokio::sync::Mutex::new(self.process_manager));
-        let pm_clone = process_manager.clone();
-        
-        let monitor_task = tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(5));
-            loop {
-                interval.tick().await;
-                if let Ok(mut pm) = pm_clone.try_lock() {
-                    if let Err(e) = pm.monitor_processes().await {
-                        tracing::error!("Error monitoring processes: {}", e);
-                    }
+        let mut process_manager = self.process_manager;
+        if let Err(e) = process_manager.resurrect().await {
+            tracing::error!("Failed to restore saved processes: {}", e);
+        }
+        let process_manager = std::sync::Arc::new(tokio::sync::Mutex::new(process_manager));
+        let monitor_task = crate::process::ProcessManager::spawn_supervisor(&process_manager).await;
+
+        let config = process_manager.lock().await.config().clone();
+        if let Some(port) = config.metrics_port {
+            match crate::metrics::bind_address(&config.metrics_bind, port) {
+                Ok(address) => {
+                    let manager = process_manager.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = crate::metrics::serve(address, manager).await {
+                            tracing::error!("Metrics server error: {}", e);
+                        }
+                    });
                 }
+                Err(e) => tracing::error!("Metrics server not started: {}", e),
             }
-        });
+        }
+
+        // logrotate's `postrotate kill -USR1` convention: reopen log files by path.
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            match signal(SignalKind::user_defined1()) {
+                Ok(mut usr1) => {
+                    tokio::spawn(async move {
+                        while usr1.recv().await.is_some() {
+                            tracing::info!("Received SIGUSR1, reopening log files");
+                            crate::logs::file_pool().lock().unwrap().reopen_all();
+                        }
+                    });
+                }
+                Err(e) => tracing::warn!("Failed to install SIGUSR1 handler: {}", e),
+            }
+        }
 
         let ipc_task = tokio::spawn(async move {
             if let Err(e) = self.ipc_server.run(process_manager).await {
@@ -98,12 +262,18 @@ impl DaemonManager {
             _ = ipc_task => {
                 tracing::info!("IPC server finished");
             }
-            _ = tokio::signal::ctrl_c() => {
+            _ = shutdown_signal() => {
                 tracing::info!("Received shutdown signal");
             }
+            _ = SHUTDOWN.notified() => {
+                tracing::info!("Shutdown requested by client");
+            }
         }
 
         tracing::info!("RPM daemon shutting down");
+        if let Err(e) = std::fs::remove_file(&pid_path) {
+            tracing::warn!("Failed to remove {}: {}", pid_path.display(), e);
+        }
         Ok(())
     }
 }
@@ -125,18 +295,27 @@ mod windows_service {
     const SERVICE_NAME: &str = "RPMDaemon";
     const SERVICE_TYPE: ServiceType = ServiceType::OWN_PROCESS;
 
-    pub async fn install_and_start_service() -> crate::Result<()> {
+    pub async fn install_and_start_service(options: &ServiceOptions) -> crate::Result<()> {
         let service_path = std::env::current_exe()
             .map_err(|e| RpmError::Daemon(format!("Failed to get current exe: {}", e)))?;
 
+        let create_args = [
+            "create".to_string(),
+            SERVICE_NAME.to_string(),
+            format!("binPath=\"{}\" --service", service_path.display()),
+            "DisplayName=RPM Process Manager".to_string(),
+            "start=auto".to_string(),
+        ];
+        if options.dry_run {
+            let sc = |args: &[String]| -> Vec<String> {
+                std::iter::once("sc".to_string()).chain(args.iter().cloned()).collect()
+            };
+            print_dry_run(None, &[sc(&create_args[..]), sc(&["start".to_string(), SERVICE_NAME.to_string()][..])]);
+            return Ok(());
+        }
+
         let mut cmd = Command::new("sc");
-        cmd.args(&[
-            "create",
-            SERVICE_NAME,
-            &format!("binPath=\"{}\" --service", service_path.display()),
-            "DisplayName=RPM Process Manager",
-            "start=auto",
-        ]);
+        cmd.args(&create_args);
 
         let output = cmd.output()
             .map_err(|e| RpmError::Daemon(format!("Failed to create service: {}", e)))?;
@@ -235,12 +414,20 @@ mod windows_service {
 mod macos_service {
     use super::*;
     use std::fs;
-    use std::path::Path;
+    use std::path::PathBuf;
 
-    pub async fn install_and_start_launchd_service() -> crate::Result<()> {
-        let service_name = "com.rpm.daemon";
-        let current_exe = std::env::current_exe()
-            .map_err(|e| RpmError::Daemon(format!("Failed to get current exe: {}", e)))?;
+    const SERVICE_NAME: &str = "com.rpm.daemon";
+
+    /// Installs a LaunchDaemon, or with `options.user` a LaunchAgent in
+    /// `~/Library/LaunchAgents` that needs no admin rights.
+    pub async fn install_and_start_launchd_service(options: &ServiceOptions) -> crate::Result<()> {
+        let current_exe = service_executable()?;
+
+        let log_dir = match &options.log_dir {
+            Some(dir) => dir.clone(),
+            None if options.user => crate::config::get_data_dir()?,
+            None => PathBuf::from("/tmp"),
+        };
 
         let plist_content = format!(
             r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -259,28 +446,47 @@ mod macos_service {
     <key>KeepAlive</key>
     <true/>
     <key>StandardOutPath</key>
-    <string>/tmp/rpm-daemon.log</string>
+    <string>{}</string>
     <key>StandardErrorPath</key>
-    <string>/tmp/rpm-daemon.err</string>
+    <string>{}</string>
 </dict>
 </plist>"#,
-            service_name,
-            current_exe.display()
+            SERVICE_NAME,
+            current_exe.display(),
+            log_dir.join("rpm-daemon.log").display(),
+            log_dir.join("rpm-daemon.err").display()
         );
 
-        let plist_path = format!("/Library/LaunchDaemons/{}.plist", service_name);
-        
+        let plist_path = plist_path(options.user)?;
+        if options.dry_run {
+            print_dry_run(Some((&plist_path, &plist_content)), &[
+                vec!["launchctl".to_string(), "load".to_string(), plist_path.display().to_string()],
+                vec!["launchctl".to_string(), "start".to_string(), SERVICE_NAME.to_string()],
+            ]);
+            return Ok(());
+        }
+
+        fs::create_dir_all(&log_dir).map_err(|e| {
+            RpmError::Daemon(format!("Failed to create log directory {}: {}", log_dir.display(), e))
+        })?;
+        if let Some(parent) = plist_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                RpmError::Daemon(format!("Failed to create {}: {}", parent.display(), e))
+            })?;
+        }
+
         fs::write(&plist_path, plist_content)
             .map_err(|e| {
                 if e.kind() == std::io::ErrorKind::PermissionDenied {
-                    RpmError::Daemon("Failed to write launchd plist: Permission denied. Please run with sudo.".to_string())
+                    RpmError::Daemon("Failed to write launchd plist: Permission denied. Please run with sudo, or pass --user.".to_string())
                 } else {
                     RpmError::Daemon(format!("Failed to write launchd plist: {}", e))
                 }
             })?;
 
+        let plist_arg = plist_path.to_string_lossy();
         let output = Command::new("launchctl")
-            .args(&["load", &plist_path])
+            .args(["load", plist_arg.as_ref()])
             .output()
             .map_err(|e| RpmError::Daemon(format!("Failed to load service: {}", e)))?;
 
@@ -291,7 +497,7 @@ mod macos_service {
         }
 
         let output = Command::new("launchctl")
-            .args(&["start", service_name])
+            .args(["start", SERVICE_NAME])
             .output()
             .map_err(|e| RpmError::Daemon(format!("Failed to start service: {}", e)))?;
 
@@ -301,23 +507,63 @@ mod macos_service {
             return Err(RpmError::Daemon(format!("Failed to start service: {} {}", error, stdout)));
         }
 
-        println!("macOS launchd service installed and started successfully");
+        if options.user {
+            println!("macOS launchd user agent installed and started successfully");
+        } else {
+            println!("macOS launchd service installed and started successfully");
+        }
+        println!("Daemon output is written to {}", log_dir.display());
         Ok(())
     }
+
+    fn plist_path(user: bool) -> crate::Result<PathBuf> {
+        let file_name = format!("{}.plist", SERVICE_NAME);
+        if !user {
+            return Ok(PathBuf::from("/Library/LaunchDaemons").join(file_name));
+        }
+
+        let base_dirs = directories::BaseDirs::new()
+            .ok_or_else(|| RpmError::Daemon("Failed to get home directory".to_string()))?;
+        Ok(base_dirs.home_dir().join("Library").join("LaunchAgents").join(file_name))
+    }
 }
 
 #[cfg(target_os = "linux")]
 mod linux_service {
     use super::*;
     use std::fs;
+    use std::path::PathBuf;
 
-    pub async fn install_and_start_systemd_service() -> crate::Result<()> {
-        let service_name = "rpm-daemon";
-        let current_exe = std::env::current_exe()
-            .map_err(|e| RpmError::Daemon(format!("Failed to get current exe: {}", e)))?;
+    const SERVICE_NAME: &str = "rpm-daemon";
+
+    /// Installs a system unit, or with `options.user` a user unit that runs
+    /// as the invoking user. A user daemon gets that user's
+    /// `XDG_RUNTIME_DIR` and data directory from systemd, the same ones the
+    /// CLI looks in, so no root-owned paths are involved.
+    pub async fn install_and_start_systemd_service(options: &ServiceOptions) -> crate::Result<()> {
+        let service_exe = service_executable()?;
+
+        let systemd_content = if options.user {
+            format!(
+                r#"[Unit]
+Description=RPM Process Manager Daemon
 
-        let systemd_content = format!(
-            r#"[Unit]
+[Service]
+Type=simple
+ExecStart={} --service
+Restart={}
+RestartSec={}
+
+[Install]
+WantedBy=default.target
+"#,
+                service_exe.display(),
+                options.restart.as_systemd(),
+                options.restart_sec
+            )
+        } else {
+            format!(
+                r#"[Unit]
 Description=RPM Process Manager Daemon
 After=network.target
 Wants=network.target
@@ -325,8 +571,8 @@ Wants=network.target
 [Service]
 Type=simple
 ExecStart={} --service
-Restart=always
-RestartSec=5
+Restart={}
+RestartSec={}
 User=root
 StandardOutput=journal
 StandardError=journal
@@ -334,52 +580,84 @@ StandardError=journal
 [Install]
 WantedBy=multi-user.target
 "#,
-            current_exe.display()
-        );
+                service_exe.display(),
+                options.restart.as_systemd(),
+                options.restart_sec
+            )
+        };
+
+        let service_path = unit_path(options.user)?;
+        if options.dry_run {
+            let systemctl = |args: &[&str]| {
+                let mut command = vec!["systemctl".to_string()];
+                if options.user {
+                    command.push("--user".to_string());
+                }
+                command.extend(args.iter().map(|arg| arg.to_string()));
+                command
+            };
+            print_dry_run(Some((&service_path, &systemd_content)), &[
+                systemctl(&["daemon-reload"]),
+                systemctl(&["enable", SERVICE_NAME]),
+                systemctl(&["start", SERVICE_NAME]),
+            ]);
+            return Ok(());
+        }
+
+        if let Some(parent) = service_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                RpmError::Daemon(format!("Failed to create {}: {}", parent.display(), e))
+            })?;
+        }
 
-        let service_path = format!("/etc/systemd/system/{}.service", service_name);
-        
         fs::write(&service_path, systemd_content)
             .map_err(|e| {
                 if e.kind() == std::io::ErrorKind::PermissionDenied {
-                    RpmError::Daemon("Failed to write systemd service file: Permission denied. Please run with sudo.".to_string())
+                    RpmError::Daemon("Failed to write systemd service file: Permission denied. Please run with sudo, or pass --user.".to_string())
                 } else {
                     RpmError::Daemon(format!("Failed to write systemd service file: {}", e))
                 }
             })?;
 
-        let output = Command::new("systemctl")
-            .args(&["daemon-reload"])
-            .output()
-            .map_err(|e| RpmError::Daemon(format!("Failed to reload systemd: {}", e)))?;
+        systemctl(options.user, &["daemon-reload"], "reload systemd")?;
+        systemctl(options.user, &["enable", SERVICE_NAME], "enable service")?;
+        systemctl(options.user, &["start", SERVICE_NAME], "start service")?;
 
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(RpmError::Daemon(format!("Failed to reload systemd: {}", error)));
+        if options.user {
+            println!("Linux systemd user service installed and started successfully");
+            println!("To keep it running while you're logged out, run: loginctl enable-linger");
+        } else {
+            println!("Linux systemd service installed and started successfully");
         }
+        Ok(())
+    }
 
-        let output = Command::new("systemctl")
-            .args(&["enable", service_name])
-            .output()
-            .map_err(|e| RpmError::Daemon(format!("Failed to enable service: {}", e)))?;
-
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(RpmError::Daemon(format!("Failed to enable service: {}", error)));
+    fn unit_path(user: bool) -> crate::Result<PathBuf> {
+        let file_name = format!("{}.service", SERVICE_NAME);
+        if !user {
+            return Ok(PathBuf::from("/etc/systemd/system").join(file_name));
         }
 
-        let output = Command::new("systemctl")
-            .args(&["start", service_name])
+        let base_dirs = directories::BaseDirs::new()
+            .ok_or_else(|| RpmError::Daemon("Failed to get home directory".to_string()))?;
+        Ok(base_dirs.config_dir().join("systemd").join("user").join(file_name))
+    }
+
+    fn systemctl(user: bool, args: &[&str], action: &str) -> crate::Result<()> {
+        let mut cmd = Command::new("systemctl");
+        if user {
+            cmd.arg("--user");
+        }
+        let output = cmd
+            .args(args)
             .output()
-            .map_err(|e| RpmError::Daemon(format!("Failed to start service: {}", e)))?;
+            .map_err(|e| RpmError::Daemon(format!("Failed to {}: {}", action, e)))?;
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
             let stdout = String::from_utf8_lossy(&output.stdout);
-            return Err(RpmError::Daemon(format!("Failed to start service: {} {}", error, stdout)));
+            return Err(RpmError::Daemon(format!("Failed to {}: {} {}", action, error, stdout)));
         }
-
-        println!("Linux systemd service installed and started successfully");
         Ok(())
     }
 }
@@ -402,11 +680,8 @@ mod unix_daemon {
             .map_err(|e| RpmError::Daemon(format!("Failed to create stdout file: {}", e)))?;
         let stderr = File::create(daemon_dir.join("daemon.err"))
             .map_err(|e| RpmError::Daemon(format!("Failed to create stderr file: {}", e)))?;
-        let pidfile = daemon_dir.join("daemon.pid");
-
+        // The daemon writes its own PID file to the runtime directory once running.
         let daemonize = Daemonize::new()
-            .pid_file(&pidfile)
-            .chown_pid_file(true)
             .working_directory(daemon_dir)
             .stdout(stdout)
             .stderr(stderr);
@@ -1,5 +1,4 @@
 use clap::Parser;
-use tokio;
 
 #[derive(Parser, Debug)]
 #[command(name = "rpm-daemon")]
@@ -13,12 +12,26 @@ struct Args {
     
     #[arg(long, help = "Install and start as system service")]
     install: bool,
+
+    #[arg(long, value_enum, help = "Where the daemon writes its own logs (overrides config)")]
+    log_sink: Option<rpm::logging::LogSink>,
+
+    #[arg(long, value_enum, help = "Daemon log format (defaults to $RPM_LOG_FORMAT, then text)")]
+    log_format: Option<rpm::logging::LogFormat>,
+
+    #[arg(short, long, action = clap::ArgAction::Count, help = "Debug output (-v), or trace output (-vv)")]
+    verbose: u8,
 }
 
 #[tokio::main]
 async fn main() -> rpm::Result<()> {
     let args = Args::parse();
-    tracing_subscriber::fmt::init();
+    let sink = match args.log_sink {
+        Some(sink) => sink,
+        None => rpm::config::Config::load().await?.daemon_log_sink,
+    };
+    let format = args.log_format.unwrap_or_else(rpm::logging::LogFormat::from_env);
+    rpm::logging::init(sink, format, args.verbose)?;
     
     if args.service {
         #[cfg(windows)]
@@ -0,0 +1,109 @@
+use crate::{cli::ProcessConfig, Result, RpmError};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// An ecosystem file is either a bare list of process definitions or an
+/// object with an `apps` list, mirroring PM2's layout. The object form may
+/// also `include` other ecosystem files, resolved relative to itself.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EcosystemFile {
+    Apps {
+        #[serde(default)]
+        apps: Vec<ProcessConfig>,
+        #[serde(default)]
+        include: Vec<String>,
+    },
+    List(Vec<ProcessConfig>),
+}
+
+pub fn is_ecosystem_file(path: &str) -> bool {
+    let path = Path::new(path);
+    path.is_file() && path.extension().is_some_and(|ext| ext == "json")
+}
+
+/// Loads every process definition from an ecosystem file and the files it
+/// includes. Each definition records the file it came from so `reload` can
+/// re-read it later, and a relative `cwd` is resolved against that file's
+/// directory.
+pub fn load(path: &Path) -> Result<Vec<ProcessConfig>> {
+    let mut configs = Vec::new();
+    load_into(path, &mut Vec::new(), &mut configs)?;
+
+    for (index, config) in configs.iter().enumerate() {
+        if let Some(other) = configs[..index].iter().find(|other| other.name == config.name) {
+            return Err(RpmError::Config(format!(
+                "Process '{}' is defined in both {} and {}",
+                config.name,
+                other.source.as_deref().unwrap_or_default(),
+                config.source.as_deref().unwrap_or_default()
+            )));
+        }
+    }
+
+    Ok(configs)
+}
+
+/// `chain` holds the files currently being loaded, outermost first, so an
+/// include that leads back to one of them is reported as a cycle.
+fn load_into(path: &Path, chain: &mut Vec<PathBuf>, configs: &mut Vec<ProcessConfig>) -> Result<()> {
+    let source = path.canonicalize().map_err(|e| {
+        RpmError::Config(format!("Failed to resolve ecosystem file {}: {}", path.display(), e))
+    })?;
+
+    if chain.contains(&source) {
+        let cycle: Vec<String> = chain
+            .iter()
+            .skip_while(|file| **file != source)
+            .chain(std::iter::once(&source))
+            .map(|file| file.display().to_string())
+            .collect();
+        return Err(RpmError::Config(format!("Ecosystem include cycle: {}", cycle.join(" -> "))));
+    }
+
+    let content = std::fs::read_to_string(&source).map_err(|e| {
+        RpmError::Config(format!("Failed to read ecosystem file {}: {}", source.display(), e))
+    })?;
+
+    let file: EcosystemFile = serde_json::from_str(&content).map_err(|e| {
+        RpmError::Config(format!("Failed to parse ecosystem file {}: {}", source.display(), e))
+    })?;
+
+    let (mut apps, include) = match file {
+        EcosystemFile::Apps { apps, include } => (apps, include),
+        EcosystemFile::List(apps) => (apps, Vec::new()),
+    };
+
+    let base_dir = source.parent().map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from("."));
+    for config in &mut apps {
+        for path in [&mut config.cwd, &mut config.out_file, &mut config.err_file].into_iter().flatten() {
+            if Path::new(path.as_str()).is_relative() {
+                *path = base_dir.join(&*path).display().to_string();
+            }
+        }
+        config.source = Some(source.display().to_string());
+    }
+    configs.extend(apps);
+
+    chain.push(source);
+    for included in include {
+        load_into(&base_dir.join(included), chain, configs)?;
+    }
+    chain.pop();
+
+    Ok(())
+}
+
+/// Re-reads a single process definition by name from its ecosystem file.
+pub fn load_one(path: &Path, name: &str) -> Result<ProcessConfig> {
+    load(path)?
+        .into_iter()
+        .find(|config| config.name == name)
+        .ok_or_else(|| {
+            RpmError::Config(format!(
+                "Process '{}' is no longer defined in {}",
+                name,
+                path.display()
+            ))
+        })
+}
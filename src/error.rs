@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, RpmError>;
@@ -24,4 +25,84 @@ pub enum RpmError {
     
     #[error("Process not found: {0}")]
     ProcessNotFound(String),
-}
\ No newline at end of file
+
+    #[error("Daemon is not running: {0}")]
+    DaemonNotRunning(String),
+}
+
+/// Exit code of `rpm is-running` for a process that exists but isn't running.
+/// No `RpmError` maps to it.
+pub const NOT_RUNNING_EXIT_CODE: i32 = 6;
+
+impl RpmError {
+    /// Process exit code the CLI uses for this error, so scripts can tell
+    /// failure types apart. 2 is left to clap for usage errors and 6 to
+    /// [`NOT_RUNNING_EXIT_CODE`].
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RpmError::DaemonNotRunning(_) => 3,
+            RpmError::ProcessNotFound(_) => 4,
+            RpmError::Ipc(_) => 5,
+            RpmError::Io(_)
+            | RpmError::Serde(_)
+            | RpmError::Process(_)
+            | RpmError::Daemon(_)
+            | RpmError::Config(_) => 1,
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            RpmError::Io(_) => ErrorKind::Io,
+            RpmError::Serde(_) => ErrorKind::Serde,
+            RpmError::Process(_) => ErrorKind::Process,
+            RpmError::Daemon(_) => ErrorKind::Daemon,
+            RpmError::Ipc(_) => ErrorKind::Ipc,
+            RpmError::Config(_) => ErrorKind::Config,
+            RpmError::ProcessNotFound(_) => ErrorKind::ProcessNotFound,
+            RpmError::DaemonNotRunning(_) => ErrorKind::DaemonNotRunning,
+        }
+    }
+
+    /// The error's message without the variant's prefix.
+    pub fn detail(&self) -> String {
+        match self {
+            RpmError::Io(e) => e.to_string(),
+            RpmError::Serde(e) => e.to_string(),
+            RpmError::Process(message)
+            | RpmError::Daemon(message)
+            | RpmError::Ipc(message)
+            | RpmError::Config(message)
+            | RpmError::ProcessNotFound(message)
+            | RpmError::DaemonNotRunning(message) => message.clone(),
+        }
+    }
+
+    /// Rebuilds an error from its kind and `detail()`.
+    pub fn from_kind(kind: ErrorKind, message: String) -> Self {
+        match kind {
+            ErrorKind::Io => RpmError::Io(std::io::Error::other(message)),
+            ErrorKind::Serde => RpmError::Serde(serde::de::Error::custom(message)),
+            ErrorKind::Process => RpmError::Process(message),
+            ErrorKind::Daemon => RpmError::Daemon(message),
+            ErrorKind::Ipc => RpmError::Ipc(message),
+            ErrorKind::Config => RpmError::Config(message),
+            ErrorKind::ProcessNotFound => RpmError::ProcessNotFound(message),
+            ErrorKind::DaemonNotRunning => RpmError::DaemonNotRunning(message),
+        }
+    }
+}
+
+/// Which `RpmError` variant an error is, so it can cross the IPC boundary
+/// and be rebuilt as the same variant on the other side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorKind {
+    Io,
+    Serde,
+    Process,
+    Daemon,
+    Ipc,
+    Config,
+    ProcessNotFound,
+    DaemonNotRunning,
+}
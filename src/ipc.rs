@@ -1,38 +1,197 @@
-use crate::{cli::ProcessConfig, process::ProcessInfo, Result, RpmError};
+use crate::{cli::ProcessConfig, error::ErrorKind, logs::LogLine, process::{ProcessEvent, ProcessInfo, ProcessSummary, RestartReason, StartResult}, Result, RpmError};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use serde::de::DeserializeOwned;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+#[cfg(windows)]
 use tokio::net::{TcpListener, TcpStream};
 #[cfg(unix)]
 use tokio::net::{UnixListener, UnixStream};
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, Mutex, Semaphore};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum IpcRequest {
-    StartProcess(ProcessConfig),
-    StopProcess(String),
-    RestartProcess(String),
+    StartProcess(Box<ProcessConfig>),
+    /// `timeout` (seconds) overrides the process's `kill_timeout`.
+    StopProcess { name: String, timeout: Option<u64> },
+    RestartProcess { name: String, update_env: bool, timeout: Option<u64> },
+    /// Restarts a group's processes one after another, waiting `wait` seconds
+    /// after each. Can't be part of a batch.
+    RollingRestart { group: String, update_env: bool, timeout: Option<u64>, wait: u64 },
+    /// Kills the process and its children with SIGKILL, without a grace period.
+    ForceKill(String),
     DeleteProcess(String),
     ListProcesses,
+    /// Just the columns of the process table; answered with `ProcessSummaries`.
+    ListProcessesSummary,
     GetProcessInfo(String),
     GetLogs { name: String, lines: usize, follow: bool },
     Monitor,
     KillDaemon,
     ReloadProcess(String),
+    /// Replaces a process's definition and restarts it, keeping its id.
+    UpdateProcess(Box<crate::cli::ProcessConfig>),
+    /// Merges `env` into a process's env, or replaces it with `replace`, and
+    /// restarts it. Done by the daemon so clients never need the unmasked env.
+    UpdateEnv { name: String, env: Vec<(String, String)>, replace: bool },
+    SetAutostart { name: String, enabled: bool },
+    /// `false` pauses auto-restarts and memory guarding for the process.
+    SetMonitored { name: String, monitored: bool },
+    SendInput { name: String, text: String },
+    /// Reopens all log files, rotating them first when `rotate` is set.
+    ReloadLogs { rotate: bool },
+    /// Seconds between monitor checks, in effect from the next check.
+    SetMonitorInterval(u64),
     SaveProcesses,
     ResurrectProcesses,
+    /// Exchanges versions; answered with `IpcResponse::Handshake`.
+    Handshake { client_version: String },
+    /// Streams the process's output as `LogLine` responses until the client
+    /// disconnects, starting with up to `backlog` recent lines from the
+    /// in-memory buffer. Only valid as the first request on a connection.
+    AttachLogs { name: String, backlog: usize },
+    /// Streams an `Event` response for every process that exits on its own
+    /// or crosses a warning threshold, until the client disconnects. Only
+    /// valid as the first request on a connection.
+    SubscribeEvents,
+    /// Runs each request in order under one lock and answers with a
+    /// `Batch` of their responses, once any processes they restarted are
+    /// back up. A failing item doesn't stop the rest.
+    Batch(Vec<IpcRequest>),
+}
+
+impl IpcRequest {
+    /// The process name or id a request acts on, if it names one.
+    fn target_mut(&mut self) -> Option<&mut String> {
+        match self {
+            IpcRequest::StopProcess { name, .. }
+            | IpcRequest::RestartProcess { name, .. }
+            | IpcRequest::DeleteProcess(name)
+            | IpcRequest::ForceKill(name)
+            | IpcRequest::GetProcessInfo(name)
+            | IpcRequest::GetLogs { name, .. }
+            | IpcRequest::ReloadProcess(name)
+            | IpcRequest::SetAutostart { name, .. }
+            | IpcRequest::SetMonitored { name, .. }
+            | IpcRequest::UpdateEnv { name, .. }
+            | IpcRequest::SendInput { name, .. }
+            | IpcRequest::AttachLogs { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// The process a request restarts, if it does.
+    fn restarted(&self) -> Option<&str> {
+        match self {
+            IpcRequest::RestartProcess { name, .. }
+            | IpcRequest::ReloadProcess(name)
+            | IpcRequest::UpdateEnv { name, .. } => Some(name),
+            IpcRequest::UpdateProcess(config) => Some(&config.name),
+            _ => None,
+        }
+    }
+
+    /// The request's variant and the process or group it names, for logging.
+    /// Leaves out everything else, such as env values in a `ProcessConfig`.
+    fn describe(&self) -> String {
+        let (variant, target) = match self {
+            IpcRequest::StartProcess(config) => ("StartProcess", Some(config.name.as_str())),
+            IpcRequest::StopProcess { name, .. } => ("StopProcess", Some(name.as_str())),
+            IpcRequest::RestartProcess { name, .. } => ("RestartProcess", Some(name.as_str())),
+            IpcRequest::RollingRestart { group, .. } => ("RollingRestart", Some(group.as_str())),
+            IpcRequest::ForceKill(name) => ("ForceKill", Some(name.as_str())),
+            IpcRequest::DeleteProcess(name) => ("DeleteProcess", Some(name.as_str())),
+            IpcRequest::ListProcesses => ("ListProcesses", None),
+            IpcRequest::ListProcessesSummary => ("ListProcessesSummary", None),
+            IpcRequest::GetProcessInfo(name) => ("GetProcessInfo", Some(name.as_str())),
+            IpcRequest::GetLogs { name, .. } => ("GetLogs", Some(name.as_str())),
+            IpcRequest::Monitor => ("Monitor", None),
+            IpcRequest::KillDaemon => ("KillDaemon", None),
+            IpcRequest::ReloadProcess(name) => ("ReloadProcess", Some(name.as_str())),
+            IpcRequest::UpdateProcess(config) => ("UpdateProcess", Some(config.name.as_str())),
+            IpcRequest::UpdateEnv { name, .. } => ("UpdateEnv", Some(name.as_str())),
+            IpcRequest::SetAutostart { name, .. } => ("SetAutostart", Some(name.as_str())),
+            IpcRequest::SetMonitored { name, .. } => ("SetMonitored", Some(name.as_str())),
+            IpcRequest::SendInput { name, .. } => ("SendInput", Some(name.as_str())),
+            IpcRequest::ReloadLogs { .. } => ("ReloadLogs", None),
+            IpcRequest::SetMonitorInterval(_) => ("SetMonitorInterval", None),
+            IpcRequest::SaveProcesses => ("SaveProcesses", None),
+            IpcRequest::ResurrectProcesses => ("ResurrectProcesses", None),
+            IpcRequest::Handshake { .. } => ("Handshake", None),
+            IpcRequest::AttachLogs { name, .. } => ("AttachLogs", Some(name.as_str())),
+            IpcRequest::SubscribeEvents => ("SubscribeEvents", None),
+            IpcRequest::Batch(requests) => return format!("Batch of {} requests", requests.len()),
+        };
+        match target {
+            Some(target) => format!("{} '{}'", variant, target),
+            None => variant.to_string(),
+        }
+    }
+}
+
+/// A failed request's error, tagged with its `RpmError` variant so the
+/// client gets back the same error the daemon hit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpcError {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl IpcError {
+    fn ipc(message: &str) -> Self {
+        Self { kind: ErrorKind::Ipc, message: message.to_string() }
+    }
+}
+
+impl From<RpmError> for IpcError {
+    fn from(error: RpmError) -> Self {
+        Self { kind: error.kind(), message: error.detail() }
+    }
+}
+
+impl From<IpcError> for RpmError {
+    fn from(error: IpcError) -> Self {
+        RpmError::from_kind(error.kind, error.message)
+    }
+}
+
+impl std::fmt::Display for IpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", RpmError::from(self.clone()))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum IpcResponse {
     Success(String),
     ProcessList(Vec<ProcessInfo>),
-    ProcessInfo(ProcessInfo),
-    Logs(Vec<String>),
-    Error(String),
+    ProcessSummaries(Vec<ProcessSummary>),
+    ProcessInfo(Box<ProcessInfo>),
+    Started(StartResult),
+    Logs(Vec<LogLine>),
+    /// Part of a large log response; more frames follow, ending with `Logs`.
+    LogChunk(Vec<LogLine>),
+    Error(IpcError),
+    Batch(Vec<IpcResponse>),
+    LogLine(LogLine),
+    Event(ProcessEvent),
+    Handshake(DaemonInfo),
+}
+
+/// What the daemon reports about itself during the handshake.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonInfo {
+    pub version: String,
+    pub pid: u32,
+    pub endpoint: String,
+    pub data_dir: String,
+    /// `None` from daemons older than this field.
+    #[serde(default)]
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 pub struct IpcServer {
+    connection_limit: Arc<Semaphore>,
     #[cfg(unix)]
     socket_path: std::path::PathBuf,
     #[cfg(windows)]
@@ -40,21 +199,41 @@ pub struct IpcServer {
 }
 
 impl IpcServer {
-    pub async fn new() -> Result<Self> {
+    /// Creates a server that handles at most `max_connections` clients at once.
+    pub async fn new(max_connections: usize) -> Result<Self> {
+        let connection_limit = Arc::new(Semaphore::new(max_connections.max(1)));
+
         #[cfg(unix)]
         {
             let socket_path = get_socket_path()?;
             if socket_path.exists() {
+                // A socket left behind by a daemon that died is removed; one
+                // that still answers belongs to a running daemon.
+                if let Ok(stream) = UnixStream::connect(&socket_path).await {
+                    let (reader, writer) = stream.into_split();
+                    if is_live_daemon(reader, writer).await {
+                        return Err(RpmError::Daemon(format!(
+                            "An RPM daemon is already running at {}",
+                            socket_path.display()
+                        )));
+                    }
+                }
                 std::fs::remove_file(&socket_path).map_err(|e| {
                     RpmError::Ipc(format!("Failed to remove existing socket: {}", e))
                 })?;
             }
-            Ok(IpcServer { socket_path })
+            Ok(IpcServer {
+                connection_limit,
+                socket_path,
+            })
         }
         
         #[cfg(windows)]
         {
-            Ok(IpcServer { port: 9999 })
+            Ok(IpcServer {
+                connection_limit,
+                port: 9999,
+            })
         }
     }
 
@@ -71,10 +250,17 @@ impl IpcServer {
             tracing::info!("IPC server listening on Unix socket: {:?}", self.socket_path);
 
             loop {
+                // Wait for a free slot before accepting, so excess clients queue
+                // in the listen backlog instead of each getting a task.
+                let permit = self.connection_limit.clone().acquire_owned().await.map_err(|e| {
+                    RpmError::Ipc(format!("Connection limit closed: {}", e))
+                })?;
+
                 match listener.accept().await {
                     Ok((stream, _)) => {
                         let pm = process_manager.clone();
                         tokio::spawn(async move {
+                            let _permit = permit;
                             if let Err(e) = handle_unix_connection(stream, pm).await {
                                 tracing::error!("Error handling Unix connection: {}", e);
                             }
@@ -89,17 +275,43 @@ impl IpcServer {
 
         #[cfg(windows)]
         {
-            let listener = TcpListener::bind(format!("127.0.0.1:{}", self.port))
-                .await
-                .map_err(|e| RpmError::Ipc(format!("Failed to bind TCP socket: {}", e)))?;
+            let address = format!("127.0.0.1:{}", self.port);
+            let listener = match TcpListener::bind(&address).await {
+                Ok(listener) => listener,
+                Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => {
+                    let running = match TcpStream::connect(&address).await {
+                        Ok(stream) => {
+                            let (reader, writer) = stream.into_split();
+                            is_live_daemon(reader, writer).await
+                        }
+                        Err(_) => false,
+                    };
+                    return Err(if running {
+                        RpmError::Daemon(format!("An RPM daemon is already running on {}", address))
+                    } else {
+                        RpmError::Daemon(format!(
+                            "Port {} is already in use by another program; stop it and start the daemon again",
+                            self.port
+                        ))
+                    });
+                }
+                Err(e) => return Err(RpmError::Ipc(format!("Failed to bind TCP socket: {}", e))),
+            };
 
             tracing::info!("IPC server listening on TCP port: {}", self.port);
 
             loop {
+                // Wait for a free slot before accepting, so excess clients queue
+                // in the listen backlog instead of each getting a task.
+                let permit = self.connection_limit.clone().acquire_owned().await.map_err(|e| {
+                    RpmError::Ipc(format!("Connection limit closed: {}", e))
+                })?;
+
                 match listener.accept().await {
                     Ok((stream, _)) => {
                         let pm = process_manager.clone();
                         tokio::spawn(async move {
+                            let _permit = permit;
                             if let Err(e) = handle_tcp_connection(stream, pm).await {
                                 tracing::error!("Error handling TCP connection: {}", e);
                             }
@@ -114,30 +326,86 @@ impl IpcServer {
     }
 }
 
-#[cfg(unix)]
-async fn handle_unix_connection(
-    stream: UnixStream,
-    process_manager: Arc<Mutex<crate::process::ProcessManager>>,
-) -> Result<()> {
-    let (reader, mut writer) = stream.into_split();
-    let mut reader = BufReader::new(reader);
-    let mut line = String::new();
+/// Largest frame either side will accept, so a bad length prefix can't make
+/// the reader allocate without bound.
+const MAX_FRAME_LEN: u32 = 64 * 1024 * 1024;
 
-    while reader.read_line(&mut line).await? > 0 {
-        let request: IpcRequest = serde_json::from_str(&line)
-            .map_err(|e| RpmError::Ipc(format!("Failed to parse request: {}", e)))?;
+/// Log lines per frame when a log response is split up.
+const LOG_CHUNK_LINES: usize = 1000;
 
-        let response = handle_request(request, &process_manager).await;
-        let response_json = serde_json::to_string(&response)
-            .map_err(|e| RpmError::Ipc(format!("Failed to serialize response: {}", e)))?;
+/// Writes one message as a big-endian `u32` byte length followed by its JSON body.
+async fn write_frame<W, T>(writer: &mut W, message: &T) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    let body = serde_json::to_vec(message)
+        .map_err(|e| RpmError::Ipc(format!("Failed to serialize message: {}", e)))?;
+    let len = u32::try_from(body.len())
+        .ok()
+        .filter(|len| *len <= MAX_FRAME_LEN)
+        .ok_or_else(|| RpmError::Ipc(format!("Message too large: {} bytes", body.len())))?;
 
-        writer.write_all(response_json.as_bytes()).await?;
-        writer.write_all(b"\n").await?;
+    writer.write_all(&len.to_be_bytes()).await?;
+    writer.write_all(&body).await?;
+    writer.flush().await?;
+    Ok(())
+}
 
-        line.clear();
+/// Reads one length-prefixed message. Returns `None` if the peer closed the
+/// connection cleanly before starting a new frame.
+async fn read_frame<R, T>(reader: &mut R) -> Result<Option<T>>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    let mut len = [0u8; 4];
+    match reader.read_exact(&mut len).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
     }
 
-    Ok(())
+    let len = u32::from_be_bytes(len);
+    if len > MAX_FRAME_LEN {
+        return Err(RpmError::Ipc(format!("Message too large: {} bytes", len)));
+    }
+
+    let mut body = vec![0u8; len as usize];
+    reader.read_exact(&mut body).await?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| RpmError::Ipc(format!("Failed to parse message: {}", e)))
+}
+
+/// Whether the other end of a connection is an RPM daemon, judged by whether
+/// it answers a handshake within a second.
+async fn is_live_daemon<R, W>(mut reader: R, mut writer: W) -> bool
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let handshake = async {
+        let request = IpcRequest::Handshake {
+            client_version: env!("CARGO_PKG_VERSION").to_string(),
+        };
+        write_frame(&mut writer, &request).await?;
+        read_frame::<_, IpcResponse>(&mut reader).await
+    };
+
+    matches!(
+        tokio::time::timeout(std::time::Duration::from_secs(1), handshake).await,
+        Ok(Ok(Some(IpcResponse::Handshake(_))))
+    )
+}
+
+#[cfg(unix)]
+async fn handle_unix_connection(
+    stream: UnixStream,
+    process_manager: Arc<Mutex<crate::process::ProcessManager>>,
+) -> Result<()> {
+    let (reader, writer) = stream.into_split();
+    serve_connection(reader, writer, process_manager).await
 }
 
 #[cfg(windows)]
@@ -145,72 +413,273 @@ async fn handle_tcp_connection(
     stream: TcpStream,
     process_manager: Arc<Mutex<crate::process::ProcessManager>>,
 ) -> Result<()> {
-    let (reader, mut writer) = stream.into_split();
-    let mut reader = BufReader::new(reader);
-    let mut line = String::new();
+    let (reader, writer) = stream.into_split();
+    serve_connection(reader, writer, process_manager).await
+}
 
-    while reader.read_line(&mut line).await? > 0 {
-        let request: IpcRequest = serde_json::from_str(&line)
-            .map_err(|e| RpmError::Ipc(format!("Failed to parse request: {}", e)))?;
+async fn serve_connection<R, W>(
+    mut reader: R,
+    mut writer: W,
+    process_manager: Arc<Mutex<crate::process::ProcessManager>>,
+) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut first = true;
+    while let Some(request) = read_frame::<_, IpcRequest>(&mut reader).await? {
+        match request {
+            IpcRequest::AttachLogs { name, backlog } if first => {
+                return stream_logs(&name, backlog, reader, writer, &process_manager).await;
+            }
+            IpcRequest::SubscribeEvents if first => {
+                return stream_events(reader, writer, &process_manager).await;
+            }
+            IpcRequest::AttachLogs { .. } | IpcRequest::SubscribeEvents => {
+                let error = IpcError::ipc("Streaming is only possible as a connection's first request");
+                write_response(&mut writer, IpcResponse::Error(error)).await?;
+                continue;
+            }
+            _ => {}
+        }
+        first = false;
 
+        let shutdown = matches!(request, IpcRequest::KillDaemon);
         let response = handle_request(request, &process_manager).await;
-        let response_json = serde_json::to_string(&response)
-            .map_err(|e| RpmError::Ipc(format!("Failed to serialize response: {}", e)))?;
+        write_response(&mut writer, response).await?;
+        // Only once the client has its answer, since the daemon exits next.
+        if shutdown {
+            crate::daemon::request_shutdown();
+            return Ok(());
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a response, sending large log slices as `LogChunk` frames followed
+/// by a final `Logs` frame so no single frame holds all of them.
+async fn write_response<W>(writer: &mut W, response: IpcResponse) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let lines = match response {
+        IpcResponse::Logs(lines) if lines.len() > LOG_CHUNK_LINES => lines,
+        response => return write_frame(writer, &response).await,
+    };
 
-        writer.write_all(response_json.as_bytes()).await?;
-        writer.write_all(b"\n").await?;
+    let mut lines = lines.into_iter();
+    while lines.len() > LOG_CHUNK_LINES {
+        let chunk: Vec<LogLine> = lines.by_ref().take(LOG_CHUNK_LINES).collect();
+        write_frame(writer, &IpcResponse::LogChunk(chunk)).await?;
+    }
+    write_frame(writer, &IpcResponse::Logs(lines.collect())).await
+}
 
-        line.clear();
+/// Turns the connection into a one-way stream of `LogLine` frames for a
+/// process. The stream ends when the client sends anything or disconnects.
+async fn stream_logs<R, W>(
+    name: &str,
+    backlog: usize,
+    mut reader: R,
+    mut writer: W,
+    process_manager: &Arc<Mutex<crate::process::ProcessManager>>,
+) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let subscription = {
+        let pm = process_manager.lock().await;
+        pm.subscribe_logs(&pm.resolve_target(name), backlog)
+    };
+    let (recent, mut lines) = match subscription {
+        Ok(subscription) => subscription,
+        Err(e) => return write_frame(&mut writer, &IpcResponse::Error(e.into())).await,
+    };
+    write_frame(&mut writer, &IpcResponse::Success(format!("Attached to '{}'", name))).await?;
+    for line in recent {
+        write_frame(&mut writer, &IpcResponse::LogLine(line)).await?;
     }
 
-    Ok(())
+    loop {
+        tokio::select! {
+            line = lines.recv() => match line {
+                Ok(line) => write_frame(&mut writer, &IpcResponse::LogLine(line)).await?,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::debug!("Attached client of '{}' skipped {} lines", name, skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            _ = read_frame::<_, IpcRequest>(&mut reader) => return Ok(()),
+        }
+    }
+}
+
+/// Turns the connection into a one-way stream of `Event` frames. The
+/// stream ends when the client sends anything or disconnects.
+async fn stream_events<R, W>(
+    mut reader: R,
+    mut writer: W,
+    process_manager: &Arc<Mutex<crate::process::ProcessManager>>,
+) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut events = process_manager.lock().await.subscribe_events();
+    write_frame(&mut writer, &IpcResponse::Success("Subscribed to events".to_string())).await?;
+
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => write_frame(&mut writer, &IpcResponse::Event(event)).await?,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::debug!("Event subscriber skipped {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            _ = read_frame::<_, IpcRequest>(&mut reader) => return Ok(()),
+        }
+    }
 }
 
 async fn handle_request(
     request: IpcRequest,
     process_manager: &Arc<Mutex<crate::process::ProcessManager>>,
 ) -> IpcResponse {
+    tracing::debug!("Handling {}", request.describe());
+    if let IpcRequest::RollingRestart { group, update_env, timeout, wait } = request {
+        // Locks the manager per step rather than for the whole rollout.
+        let restarted = crate::process::ProcessManager::rolling_restart(
+            process_manager,
+            &group,
+            update_env,
+            timeout.map(std::time::Duration::from_secs),
+            std::time::Duration::from_secs(wait),
+        )
+        .await;
+        return match restarted {
+            Ok(count) => IpcResponse::Success(format!("Restarted {} processes in group '{}'", count, group)),
+            Err(e) => IpcResponse::Error(e.into()),
+        };
+    }
+
     let mut pm = process_manager.lock().await;
 
+    match request {
+        IpcRequest::Batch(requests) => {
+            let mut responses = Vec::with_capacity(requests.len());
+            let mut restarted = Vec::with_capacity(requests.len());
+            for mut request in requests {
+                if let Some(target) = request.target_mut() {
+                    *target = pm.resolve_target(target);
+                }
+                restarted.push(request.restarted().map(str::to_string));
+                responses.push(match request {
+                    IpcRequest::Batch(_) => {
+                        IpcResponse::Error(IpcError::ipc("Nested batches are not supported"))
+                    }
+                    request => execute_request(request, &mut pm).await,
+                });
+            }
+            drop(pm);
+
+            let mut finished = Vec::with_capacity(responses.len());
+            for (response, name) in responses.into_iter().zip(restarted) {
+                finished.push(finish_restart(process_manager, name, response).await);
+            }
+            IpcResponse::Batch(finished)
+        }
+        mut request => {
+            if let Some(target) = request.target_mut() {
+                *target = pm.resolve_target(target);
+            }
+            let restarted = request.restarted().map(str::to_string);
+            let response = execute_request(request, &mut pm).await;
+            drop(pm);
+            finish_restart(process_manager, restarted, response).await
+        }
+    }
+}
+
+/// Restarts only schedule the new run, so once the manager is unlocked this
+/// waits for it and turns a failed start into the request's error.
+async fn finish_restart(
+    process_manager: &Arc<Mutex<crate::process::ProcessManager>>,
+    restarted: Option<String>,
+    response: IpcResponse,
+) -> IpcResponse {
+    match (restarted, response) {
+        (Some(name), IpcResponse::Success(message)) => {
+            match crate::process::ProcessManager::wait_for_restart(process_manager, &name).await {
+                Ok(()) => IpcResponse::Success(message),
+                Err(e) => IpcResponse::Error(e.into()),
+            }
+        }
+        (_, response) => response,
+    }
+}
+
+async fn execute_request(
+    mut request: IpcRequest,
+    pm: &mut crate::process::ProcessManager,
+) -> IpcResponse {
+    if let Some(target) = request.target_mut() {
+        *target = pm.resolve_target(target);
+    }
+
     match request {
         IpcRequest::StartProcess(config) => {
-            match pm.start_process(config).await {
-                Ok(id) => IpcResponse::Success(format!("Process started with id: {}", id)),
-                Err(e) => IpcResponse::Error(e.to_string()),
+            match pm.start_process(*config).await {
+                Ok(started) => IpcResponse::Started(started),
+                Err(e) => IpcResponse::Error(e.into()),
             }
         }
-        IpcRequest::StopProcess(name) => {
-            match pm.stop_process(&name).await {
+        IpcRequest::StopProcess { name, timeout } => {
+            match pm.stop_process(&name, timeout.map(std::time::Duration::from_secs)).await {
                 Ok(_) => IpcResponse::Success(format!("Process '{}' stopped", name)),
-                Err(e) => IpcResponse::Error(e.to_string()),
+                Err(e) => IpcResponse::Error(e.into()),
             }
         }
-        IpcRequest::RestartProcess(name) => {
-            match pm.restart_process(&name).await {
+        IpcRequest::RestartProcess { name, update_env, timeout } => {
+            if update_env {
+                if let Err(e) = pm.refresh_env(&name).await {
+                    return IpcResponse::Error(e.into());
+                }
+            }
+            match pm.restart_process(&name, timeout.map(std::time::Duration::from_secs), RestartReason::Manual).await {
                 Ok(_) => IpcResponse::Success(format!("Process '{}' restarted", name)),
-                Err(e) => IpcResponse::Error(e.to_string()),
+                Err(e) => IpcResponse::Error(e.into()),
+            }
+        }
+        IpcRequest::ForceKill(name) => {
+            match pm.force_kill_process(&name).await {
+                Ok(_) => IpcResponse::Success(format!("Process '{}' killed", name)),
+                Err(e) => IpcResponse::Error(e.into()),
             }
         }
         IpcRequest::DeleteProcess(name) => {
             match pm.delete_process(&name).await {
                 Ok(_) => IpcResponse::Success(format!("Process '{}' deleted", name)),
-                Err(e) => IpcResponse::Error(e.to_string()),
+                Err(e) => IpcResponse::Error(e.into()),
             }
         }
         IpcRequest::ListProcesses => {
             let processes = pm.list_processes().await;
-            IpcResponse::ProcessList(processes.into_iter().cloned().collect())
+            IpcResponse::ProcessList(processes)
         }
+        IpcRequest::ListProcessesSummary => IpcResponse::ProcessSummaries(pm.list_summaries()),
         IpcRequest::GetProcessInfo(name) => {
             match pm.get_process_info(&name).await {
-                Ok(info) => IpcResponse::ProcessInfo(info.clone()),
-                Err(e) => IpcResponse::Error(e.to_string()),
+                Ok(info) => IpcResponse::ProcessInfo(Box::new(info)),
+                Err(e) => IpcResponse::Error(e.into()),
             }
         }
         IpcRequest::GetLogs { name, lines, follow: _ } => {
             match pm.get_logs(&name, lines).await {
                 Ok(logs) => IpcResponse::Logs(logs),
-                Err(e) => IpcResponse::Error(e.to_string()),
+                Err(e) => IpcResponse::Error(e.into()),
             }
         }
         IpcRequest::Monitor => {
@@ -220,20 +689,154 @@ async fn handle_request(
             IpcResponse::Success("Daemon shutdown requested".to_string())
         }
         IpcRequest::ReloadProcess(name) => {
-            match pm.restart_process(&name).await {
+            match pm.reload_process(&name).await {
                 Ok(_) => IpcResponse::Success(format!("Process '{}' reloaded", name)),
-                Err(e) => IpcResponse::Error(e.to_string()),
+                Err(e) => IpcResponse::Error(e.into()),
+            }
+        }
+        IpcRequest::UpdateProcess(config) => {
+            let name = config.name.clone();
+            match pm.update_process(*config).await {
+                Ok(_) => IpcResponse::Success(format!("Process '{}' updated", name)),
+                Err(e) => IpcResponse::Error(e.into()),
+            }
+        }
+        IpcRequest::UpdateEnv { name, env, replace } => {
+            match pm.update_env(&name, env, replace).await {
+                Ok(count) => IpcResponse::Success(format!(
+                    "Process '{}' restarted with {} env variable(s)",
+                    name, count
+                )),
+                Err(e) => IpcResponse::Error(e.into()),
+            }
+        }
+        IpcRequest::SetMonitored { name, monitored } => {
+            match pm.set_monitored(&name, monitored) {
+                Ok(_) => IpcResponse::Success(format!(
+                    "Monitoring {} for '{}'",
+                    if monitored { "resumed" } else { "paused" },
+                    name
+                )),
+                Err(e) => IpcResponse::Error(e.into()),
+            }
+        }
+        IpcRequest::SetAutostart { name, enabled } => {
+            match pm.set_autostart(&name, enabled).await {
+                Ok(_) => IpcResponse::Success(format!(
+                    "Autostart {} for '{}'",
+                    if enabled { "enabled" } else { "disabled" },
+                    name
+                )),
+                Err(e) => IpcResponse::Error(e.into()),
+            }
+        }
+        IpcRequest::SendInput { name, text } => {
+            match pm.send_input(&name, &text).await {
+                Ok(_) => IpcResponse::Success(format!("Input sent to '{}'", name)),
+                Err(e) => IpcResponse::Error(e.into()),
+            }
+        }
+        IpcRequest::ReloadLogs { rotate } => {
+            match pm.reload_logs(rotate) {
+                Ok(_) => IpcResponse::Success("Log files reopened".to_string()),
+                Err(e) => IpcResponse::Error(e.into()),
+            }
+        }
+        IpcRequest::SetMonitorInterval(secs) => {
+            match pm.set_monitor_interval(secs) {
+                Ok(_) => IpcResponse::Success(format!("Monitor interval set to {}s", secs)),
+                Err(e) => IpcResponse::Error(e.into()),
             }
         }
         IpcRequest::SaveProcesses => {
             IpcResponse::Success("Processes saved".to_string())
         }
         IpcRequest::ResurrectProcesses => {
-            match pm.load_state().await {
-                Ok(_) => IpcResponse::Success("Processes resurrected".to_string()),
-                Err(e) => IpcResponse::Error(e.to_string()),
+            match pm.resurrect().await {
+                Ok(restored) => IpcResponse::Success(format!("Resurrected {} process(es)", restored)),
+                Err(e) => IpcResponse::Error(e.into()),
+            }
+        }
+        IpcRequest::Handshake { client_version } => {
+            if client_version != env!("CARGO_PKG_VERSION") {
+                tracing::warn!(
+                    "Client version {} differs from daemon version {}",
+                    client_version,
+                    env!("CARGO_PKG_VERSION")
+                );
+            }
+            match (endpoint(), crate::config::get_data_dir()) {
+                (Ok(endpoint), Ok(data_dir)) => IpcResponse::Handshake(DaemonInfo {
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    pid: std::process::id(),
+                    endpoint,
+                    data_dir: data_dir.display().to_string(),
+                    started_at: crate::daemon::started_at(),
+                }),
+                (Err(e), _) | (_, Err(e)) => IpcResponse::Error(e.into()),
             }
         }
+        IpcRequest::Batch(_) => unreachable!("batches are unpacked by handle_request"),
+        IpcRequest::AttachLogs { .. } => {
+            IpcResponse::Error(IpcError::ipc("Attaching can't be batched"))
+        }
+        IpcRequest::SubscribeEvents => {
+            IpcResponse::Error(IpcError::ipc("Subscribing can't be batched"))
+        }
+        IpcRequest::RollingRestart { .. } => {
+            IpcResponse::Error(IpcError::ipc("Rolling restarts can't be batched"))
+        }
+    }
+}
+
+const CONNECT_GRACE_PERIOD: std::time::Duration = std::time::Duration::from_secs(2);
+const CONNECT_INITIAL_BACKOFF: std::time::Duration = std::time::Duration::from_millis(50);
+const CONNECT_MAX_BACKOFF: std::time::Duration = std::time::Duration::from_millis(500);
+
+type BoxedReader = Box<dyn AsyncRead + Unpin + Send>;
+type BoxedWriter = Box<dyn AsyncWrite + Unpin + Send>;
+
+/// A live feed of one process's output, from `IpcClient::attach_logs`.
+pub struct LogSubscription {
+    reader: BoxedReader,
+    // Held open: the daemon ends the stream once this side closes.
+    writer: BoxedWriter,
+}
+
+impl LogSubscription {
+    /// Waits for the next line. Returns `None` once the daemon ends the stream.
+    pub async fn next_line(&mut self) -> Result<Option<LogLine>> {
+        match read_frame(&mut self.reader).await? {
+            Some(IpcResponse::LogLine(line)) => Ok(Some(line)),
+            Some(IpcResponse::Error(e)) => Err(e.into()),
+            Some(_) => Err(RpmError::Ipc("Unexpected response".to_string())),
+            None => Ok(None),
+        }
+    }
+
+    /// Tells the daemon to stop streaming.
+    pub async fn detach(mut self) -> Result<()> {
+        self.writer.shutdown().await?;
+        Ok(())
+    }
+}
+
+/// A live feed of crash and threshold events, from `IpcClient::subscribe_events`.
+pub struct EventSubscription {
+    reader: BoxedReader,
+    // Held open: the daemon ends the stream once this side closes.
+    _writer: BoxedWriter,
+}
+
+impl EventSubscription {
+    /// Waits for the next event. Returns `None` once the daemon ends the stream.
+    pub async fn next_event(&mut self) -> Result<Option<ProcessEvent>> {
+        match read_frame(&mut self.reader).await? {
+            Some(IpcResponse::Event(event)) => Ok(Some(event)),
+            Some(IpcResponse::Error(e)) => Err(e.into()),
+            Some(_) => Err(RpmError::Ipc("Unexpected response".to_string())),
+            None => Ok(None),
+        }
     }
 }
 
@@ -258,68 +861,175 @@ impl IpcClient {
         }
     }
 
-    async fn send_request(&self, request: IpcRequest) -> Result<IpcResponse> {
-        #[cfg(unix)]
-        {
-            let stream = UnixStream::connect(&self.socket_path).await.map_err(|e| {
-                RpmError::Ipc(format!("Failed to connect to daemon: {}", e))
-            })?;
-
-            let (reader, mut writer) = stream.into_split();
-            let mut reader = BufReader::new(reader);
+    /// Connects to the daemon, retrying with backoff for a short grace period
+    /// while nothing is listening yet, so commands issued right after
+    /// `rpm daemon` don't fail while the daemon is still binding its socket.
+    async fn connect(&self) -> Result<(BoxedReader, BoxedWriter)> {
+        let deadline = tokio::time::Instant::now() + CONNECT_GRACE_PERIOD;
+        let mut delay = CONNECT_INITIAL_BACKOFF;
 
-            let request_json = serde_json::to_string(&request)?;
-            writer.write_all(request_json.as_bytes()).await?;
-            writer.write_all(b"\n").await?;
+        loop {
+            match self.connect_once().await {
+                Err(RpmError::DaemonNotRunning(reason)) => {
+                    tracing::debug!("Daemon not reachable yet: {}", reason);
+                    if tokio::time::Instant::now() + delay > deadline {
+                        return Err(RpmError::DaemonNotRunning(format!(
+                            "{} (start it with `rpm daemon`)",
+                            reason
+                        )));
+                    }
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(CONNECT_MAX_BACKOFF);
+                }
+                result => return result,
+            }
+        }
+    }
 
-            let mut line = String::new();
-            reader.read_line(&mut line).await?;
+    async fn connect_once(&self) -> Result<(BoxedReader, BoxedWriter)> {
+        #[cfg(unix)]
+        {
+            let stream = UnixStream::connect(&self.socket_path)
+                .await
+                .map_err(|e| connect_error(e, &self.socket_path.display().to_string()))?;
 
-            let response: IpcResponse = serde_json::from_str(&line)?;
-            Ok(response)
+            let (reader, writer) = stream.into_split();
+            Ok((Box::new(reader), Box::new(writer)))
         }
 
         #[cfg(windows)]
         {
-            let stream = TcpStream::connect(format!("127.0.0.1:{}", self.port))
+            let address = format!("127.0.0.1:{}", self.port);
+            let stream = TcpStream::connect(&address)
                 .await
-                .map_err(|e| RpmError::Ipc(format!("Failed to connect to daemon: {}", e)))?;
+                .map_err(|e| connect_error(e, &address))?;
+
+            let (reader, writer) = stream.into_split();
+            Ok((Box::new(reader), Box::new(writer)))
+        }
+    }
 
-            let (reader, mut writer) = stream.into_split();
-            let mut reader = BufReader::new(reader);
+    async fn send_request(&self, request: IpcRequest) -> Result<IpcResponse> {
+        let (mut reader, mut writer) = self.connect().await?;
+        tracing::debug!("Sending {}", request.describe());
+        write_frame(&mut writer, &request).await?;
 
-            let request_json = serde_json::to_string(&request)?;
-            writer.write_all(request_json.as_bytes()).await?;
-            writer.write_all(b"\n").await?;
+        let mut chunks: Vec<LogLine> = Vec::new();
+        loop {
+            let response = read_frame(&mut reader)
+                .await?
+                .ok_or_else(|| RpmError::Ipc("Daemon closed the connection".to_string()))?;
+            let response = match response {
+                IpcResponse::LogChunk(lines) => {
+                    chunks.extend(lines);
+                    continue;
+                }
+                IpcResponse::Logs(lines) if !chunks.is_empty() => {
+                    chunks.extend(lines);
+                    IpcResponse::Logs(chunks)
+                }
+                response => response,
+            };
+            tracing::trace!("Received {:?}", response);
+            return Ok(response);
+        }
+    }
 
-            let mut line = String::new();
-            reader.read_line(&mut line).await?;
+    /// Opens a dedicated connection that receives the process's output live,
+    /// starting with up to `backlog` of its most recent lines.
+    pub async fn attach_logs(&self, name: &str, backlog: usize) -> Result<LogSubscription> {
+        let (mut reader, mut writer) = self.connect().await?;
+        let request = IpcRequest::AttachLogs {
+            name: name.to_string(),
+            backlog,
+        };
+        write_frame(&mut writer, &request).await?;
 
-            let response: IpcResponse = serde_json::from_str(&line)?;
-            Ok(response)
+        match read_frame(&mut reader).await? {
+            Some(IpcResponse::Success(_)) => Ok(LogSubscription { reader, writer }),
+            Some(IpcResponse::Error(e)) => Err(e.into()),
+            _ => Err(RpmError::Ipc("Unexpected response".to_string())),
+        }
+    }
+
+    /// Opens a dedicated connection that receives process events as they happen.
+    pub async fn subscribe_events(&self) -> Result<EventSubscription> {
+        let (mut reader, mut writer) = self.connect().await?;
+        write_frame(&mut writer, &IpcRequest::SubscribeEvents).await?;
+
+        match read_frame(&mut reader).await? {
+            Some(IpcResponse::Success(_)) => Ok(EventSubscription { reader, _writer: writer }),
+            Some(IpcResponse::Error(e)) => Err(e.into()),
+            _ => Err(RpmError::Ipc("Unexpected response".to_string())),
         }
     }
 
-    pub async fn start_process(&self, config: ProcessConfig) -> Result<()> {
-        match self.send_request(IpcRequest::StartProcess(config)).await? {
+    pub async fn start_process(&self, config: ProcessConfig) -> Result<StartResult> {
+        match self.send_request(IpcRequest::StartProcess(Box::new(config))).await? {
+            IpcResponse::Started(started) => Ok(started),
+            IpcResponse::Error(e) => Err(e.into()),
+            _ => Err(RpmError::Ipc("Unexpected response".to_string())),
+        }
+    }
+
+    pub async fn reload_logs(&self, rotate: bool) -> Result<()> {
+        match self.send_request(IpcRequest::ReloadLogs { rotate }).await? {
             IpcResponse::Success(_) => Ok(()),
-            IpcResponse::Error(e) => Err(RpmError::Ipc(e)),
+            IpcResponse::Error(e) => Err(e.into()),
             _ => Err(RpmError::Ipc("Unexpected response".to_string())),
         }
     }
 
-    pub async fn stop_process(&self, name: &str) -> Result<()> {
-        match self.send_request(IpcRequest::StopProcess(name.to_string())).await? {
+    pub async fn set_monitor_interval(&self, secs: u64) -> Result<()> {
+        match self.send_request(IpcRequest::SetMonitorInterval(secs)).await? {
             IpcResponse::Success(_) => Ok(()),
-            IpcResponse::Error(e) => Err(RpmError::Ipc(e)),
+            IpcResponse::Error(e) => Err(e.into()),
             _ => Err(RpmError::Ipc("Unexpected response".to_string())),
         }
     }
 
-    pub async fn restart_process(&self, name: &str) -> Result<()> {
-        match self.send_request(IpcRequest::RestartProcess(name.to_string())).await? {
+    pub async fn stop_process(&self, name: &str, timeout: Option<u64>) -> Result<()> {
+        let request = IpcRequest::StopProcess {
+            name: name.to_string(),
+            timeout,
+        };
+        match self.send_request(request).await? {
             IpcResponse::Success(_) => Ok(()),
-            IpcResponse::Error(e) => Err(RpmError::Ipc(e)),
+            IpcResponse::Error(e) => Err(e.into()),
+            _ => Err(RpmError::Ipc("Unexpected response".to_string())),
+        }
+    }
+
+    pub async fn restart_process(&self, name: &str, update_env: bool, timeout: Option<u64>) -> Result<()> {
+        let request = IpcRequest::RestartProcess {
+            name: name.to_string(),
+            update_env,
+            timeout,
+        };
+        match self.send_request(request).await? {
+            IpcResponse::Success(_) => Ok(()),
+            IpcResponse::Error(e) => Err(e.into()),
+            _ => Err(RpmError::Ipc("Unexpected response".to_string())),
+        }
+    }
+
+    /// Returns the daemon's summary, e.g. "Restarted 3 processes in group 'api'".
+    pub async fn rolling_restart(
+        &self,
+        group: &str,
+        update_env: bool,
+        timeout: Option<u64>,
+        wait: u64,
+    ) -> Result<String> {
+        let request = IpcRequest::RollingRestart {
+            group: group.to_string(),
+            update_env,
+            timeout,
+            wait,
+        };
+        match self.send_request(request).await? {
+            IpcResponse::Success(message) => Ok(message),
+            IpcResponse::Error(e) => Err(e.into()),
             _ => Err(RpmError::Ipc("Unexpected response".to_string())),
         }
     }
@@ -327,7 +1037,18 @@ impl IpcClient {
     pub async fn delete_process(&self, name: &str) -> Result<()> {
         match self.send_request(IpcRequest::DeleteProcess(name.to_string())).await? {
             IpcResponse::Success(_) => Ok(()),
-            IpcResponse::Error(e) => Err(RpmError::Ipc(e)),
+            IpcResponse::Error(e) => Err(e.into()),
+            _ => Err(RpmError::Ipc("Unexpected response".to_string())),
+        }
+    }
+
+    pub async fn handshake(&self) -> Result<DaemonInfo> {
+        let request = IpcRequest::Handshake {
+            client_version: env!("CARGO_PKG_VERSION").to_string(),
+        };
+        match self.send_request(request).await? {
+            IpcResponse::Handshake(info) => Ok(info),
+            IpcResponse::Error(e) => Err(e.into()),
             _ => Err(RpmError::Ipc("Unexpected response".to_string())),
         }
     }
@@ -335,27 +1056,35 @@ impl IpcClient {
     pub async fn list_processes(&self) -> Result<Vec<ProcessInfo>> {
         match self.send_request(IpcRequest::ListProcesses).await? {
             IpcResponse::ProcessList(processes) => Ok(processes),
-            IpcResponse::Error(e) => Err(RpmError::Ipc(e)),
+            IpcResponse::Error(e) => Err(e.into()),
+            _ => Err(RpmError::Ipc("Unexpected response".to_string())),
+        }
+    }
+
+    pub async fn list_summaries(&self) -> Result<Vec<ProcessSummary>> {
+        match self.send_request(IpcRequest::ListProcessesSummary).await? {
+            IpcResponse::ProcessSummaries(processes) => Ok(processes),
+            IpcResponse::Error(e) => Err(e.into()),
             _ => Err(RpmError::Ipc("Unexpected response".to_string())),
         }
     }
 
     pub async fn get_process_info(&self, name: &str) -> Result<ProcessInfo> {
         match self.send_request(IpcRequest::GetProcessInfo(name.to_string())).await? {
-            IpcResponse::ProcessInfo(info) => Ok(info),
-            IpcResponse::Error(e) => Err(RpmError::Ipc(e)),
+            IpcResponse::ProcessInfo(info) => Ok(*info),
+            IpcResponse::Error(e) => Err(e.into()),
             _ => Err(RpmError::Ipc("Unexpected response".to_string())),
         }
     }
 
-    pub async fn get_logs(&self, name: &str, lines: usize, follow: bool) -> Result<Vec<String>> {
+    pub async fn get_logs(&self, name: &str, lines: usize, follow: bool) -> Result<Vec<LogLine>> {
         match self.send_request(IpcRequest::GetLogs {
             name: name.to_string(),
             lines,
             follow,
         }).await? {
             IpcResponse::Logs(logs) => Ok(logs),
-            IpcResponse::Error(e) => Err(RpmError::Ipc(e)),
+            IpcResponse::Error(e) => Err(e.into()),
             _ => Err(RpmError::Ipc("Unexpected response".to_string())),
         }
     }
@@ -364,7 +1093,7 @@ impl IpcClient {
     pub async fn kill_daemon(&self) -> Result<()> {
         match self.send_request(IpcRequest::KillDaemon).await? {
             IpcResponse::Success(_) => Ok(()),
-            IpcResponse::Error(e) => Err(RpmError::Ipc(e)),
+            IpcResponse::Error(e) => Err(e.into()),
             _ => Err(RpmError::Ipc("Unexpected response".to_string())),
         }
     }
@@ -372,7 +1101,57 @@ impl IpcClient {
     pub async fn reload_process(&self, name: &str) -> Result<()> {
         match self.send_request(IpcRequest::ReloadProcess(name.to_string())).await? {
             IpcResponse::Success(_) => Ok(()),
-            IpcResponse::Error(e) => Err(RpmError::Ipc(e)),
+            IpcResponse::Error(e) => Err(e.into()),
+            _ => Err(RpmError::Ipc("Unexpected response".to_string())),
+        }
+    }
+
+    pub async fn update_process(&self, config: ProcessConfig) -> Result<()> {
+        match self.send_request(IpcRequest::UpdateProcess(Box::new(config))).await? {
+            IpcResponse::Success(_) => Ok(()),
+            IpcResponse::Error(e) => Err(e.into()),
+            _ => Err(RpmError::Ipc("Unexpected response".to_string())),
+        }
+    }
+
+    pub async fn update_env(&self, name: &str, env: Vec<(String, String)>, replace: bool) -> Result<String> {
+        let request = IpcRequest::UpdateEnv { name: name.to_string(), env, replace };
+        match self.send_request(request).await? {
+            IpcResponse::Success(message) => Ok(message),
+            IpcResponse::Error(e) => Err(e.into()),
+            _ => Err(RpmError::Ipc("Unexpected response".to_string())),
+        }
+    }
+
+    pub async fn set_autostart(&self, name: &str, enabled: bool) -> Result<()> {
+        match self.send_request(IpcRequest::SetAutostart {
+            name: name.to_string(),
+            enabled,
+        }).await? {
+            IpcResponse::Success(_) => Ok(()),
+            IpcResponse::Error(e) => Err(e.into()),
+            _ => Err(RpmError::Ipc("Unexpected response".to_string())),
+        }
+    }
+
+    pub async fn set_monitored(&self, name: &str, monitored: bool) -> Result<()> {
+        match self.send_request(IpcRequest::SetMonitored {
+            name: name.to_string(),
+            monitored,
+        }).await? {
+            IpcResponse::Success(_) => Ok(()),
+            IpcResponse::Error(e) => Err(e.into()),
+            _ => Err(RpmError::Ipc("Unexpected response".to_string())),
+        }
+    }
+
+    pub async fn send_input(&self, name: &str, text: &str) -> Result<()> {
+        match self.send_request(IpcRequest::SendInput {
+            name: name.to_string(),
+            text: text.to_string(),
+        }).await? {
+            IpcResponse::Success(_) => Ok(()),
+            IpcResponse::Error(e) => Err(e.into()),
             _ => Err(RpmError::Ipc("Unexpected response".to_string())),
         }
     }
@@ -380,28 +1159,56 @@ impl IpcClient {
     pub async fn save_processes(&self) -> Result<()> {
         match self.send_request(IpcRequest::SaveProcesses).await? {
             IpcResponse::Success(_) => Ok(()),
-            IpcResponse::Error(e) => Err(RpmError::Ipc(e)),
+            IpcResponse::Error(e) => Err(e.into()),
+            _ => Err(RpmError::Ipc("Unexpected response".to_string())),
+        }
+    }
+
+    /// Sends several requests over one connection. The responses are returned
+    /// in request order; individual failures come back as `IpcResponse::Error`.
+    pub async fn batch(&self, requests: Vec<IpcRequest>) -> Result<Vec<IpcResponse>> {
+        let count = requests.len();
+        match self.send_request(IpcRequest::Batch(requests)).await? {
+            IpcResponse::Batch(responses) if responses.len() == count => Ok(responses),
+            IpcResponse::Error(e) => Err(e.into()),
             _ => Err(RpmError::Ipc("Unexpected response".to_string())),
         }
     }
 
-    pub async fn resurrect_processes(&self) -> Result<()> {
+    pub async fn resurrect_processes(&self) -> Result<String> {
         match self.send_request(IpcRequest::ResurrectProcesses).await? {
-            IpcResponse::Success(_) => Ok(()),
-            IpcResponse::Error(e) => Err(RpmError::Ipc(e)),
+            IpcResponse::Success(message) => Ok(message),
+            IpcResponse::Error(e) => Err(e.into()),
             _ => Err(RpmError::Ipc("Unexpected response".to_string())),
         }
     }
 }
 
+/// Nothing listening at the endpoint means the daemon isn't running; anything
+/// else is reported as a plain IPC failure.
+fn connect_error(error: std::io::Error, endpoint: &str) -> RpmError {
+    match error.kind() {
+        std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused => {
+            RpmError::DaemonNotRunning(format!("nothing is listening at {}", endpoint))
+        }
+        _ => RpmError::Ipc(format!("Failed to connect to daemon: {}", error)),
+    }
+}
+
+/// Where the daemon listens: the socket path on Unix, the TCP address on Windows.
+pub fn endpoint() -> Result<String> {
+    #[cfg(unix)]
+    {
+        Ok(get_socket_path()?.display().to_string())
+    }
+
+    #[cfg(windows)]
+    {
+        Ok("127.0.0.1:9999".to_string())
+    }
+}
+
 #[cfg(unix)]
 fn get_socket_path() -> Result<std::path::PathBuf> {
-    let home_dir = directories::ProjectDirs::from("", "", "rpm")
-        .ok_or_else(|| RpmError::Ipc("Failed to get home directory".to_string()))?;
-    
-    let socket_dir = home_dir.runtime_dir().unwrap_or_else(|| home_dir.data_dir());
-    std::fs::create_dir_all(socket_dir)
-        .map_err(|e| RpmError::Ipc(format!("Failed to create socket directory: {}", e)))?;
-    
-    Ok(socket_dir.join("rpm.sock"))
+    Ok(crate::config::get_runtime_dir()?.join("rpm.sock"))
 }
\ No newline at end of file
@@ -1,9 +1,60 @@
+//! RPM's process manager, usable as a library as well as through the `rpm`
+//! CLI and `rpm-daemon`.
+//!
+//! [`process::ProcessManager`] owns the managed processes and works without
+//! the daemon or IPC: start, stop, list and read logs of processes with
+//! [`start_process`], [`stop_process`], [`list_processes`] and [`get_logs`],
+//! and receive crash and resource warning events from [`subscribe_events`]. Restarting crashed
+//! processes, starting restarted ones once their delay has passed and
+//! enforcing limits happen in [`spawn_supervisor`], which
+//! needs the manager behind an `Arc<tokio::sync::Mutex<_>>`; the daemon is one
+//! such embedding, serving IPC requests on top of it.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use rpm::{cli::ProcessConfig, process::{ProcessEvent, ProcessManager}};
+//!
+//! # async fn run() -> rpm::Result<()> {
+//! let manager = Arc::new(tokio::sync::Mutex::new(ProcessManager::new().await?));
+//! let _supervisor = ProcessManager::spawn_supervisor(&manager).await;
+//! let mut events = manager.lock().await.subscribe_events();
+//!
+//! let started = manager.lock().await.start_process(ProcessConfig::new("api", "node server.js")).await?;
+//! println!("started api as {} with pid {:?}", started.numeric_id, started.pid);
+//! for line in manager.lock().await.get_logs("api", 20).await? {
+//!     println!("{}", line.format());
+//! }
+//!
+//! if let Ok(ProcessEvent::Crash(crash)) = events.recv().await {
+//!     println!("{} exited with {:?}", crash.name, crash.exit_code);
+//! }
+//! manager.lock().await.stop_process("api", None).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`start_process`]: process::ProcessManager::start_process
+//! [`stop_process`]: process::ProcessManager::stop_process
+//! [`list_processes`]: process::ProcessManager::list_processes
+//! [`get_logs`]: process::ProcessManager::get_logs
+//! [`subscribe_events`]: process::ProcessManager::subscribe_events
+//! [`spawn_supervisor`]: process::ProcessManager::spawn_supervisor
+
+#[cfg(target_os = "linux")]
+pub mod cgroup;
 pub mod cli;
 pub mod daemon;
 pub mod process;
 pub mod config;
 pub mod ipc;
+pub mod ecosystem;
 pub mod error;
+pub mod logging;
+pub mod logs;
+pub mod metrics;
+pub mod pattern;
+pub mod template;
 pub mod ui;
+pub mod watch;
 
 pub use error::{Result, RpmError};
\ No newline at end of file
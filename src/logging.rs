@@ -0,0 +1,300 @@
+use crate::{Result, RpmError};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::sync::Mutex;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer, Registry};
+
+/// Environment variable that selects the daemon log format when no flag is given.
+pub const LOG_FORMAT_ENV: &str = "RPM_LOG_FORMAT";
+
+/// Where the daemon sends its own tracing output.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogSink {
+    /// Write to standard output (the default).
+    #[default]
+    Stdout,
+    /// Write to syslog on Unix or the Event Log on Windows.
+    System,
+    /// Append to `daemon.log` in the data directory.
+    File,
+}
+
+/// How each daemon log event is rendered.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines (the default).
+    #[default]
+    Text,
+    /// One JSON object per event, for log pipelines.
+    Json,
+}
+
+impl LogFormat {
+    /// Reads the format from `RPM_LOG_FORMAT`, falling back to text.
+    pub fn from_env() -> Self {
+        match std::env::var(LOG_FORMAT_ENV) {
+            Ok(value) if value.eq_ignore_ascii_case("json") => LogFormat::Json,
+            _ => LogFormat::Text,
+        }
+    }
+}
+
+/// The tracing filter for `-v` flags: none defers to `RUST_LOG`, `-v` turns
+/// on RPM's debug output and `-vv` its trace output.
+pub fn env_filter(verbosity: u8) -> EnvFilter {
+    match verbosity {
+        0 => EnvFilter::from_default_env(),
+        1 => EnvFilter::new("info,rpm=debug"),
+        _ => EnvFilter::new("debug,rpm=trace"),
+    }
+}
+
+/// Sets up tracing for the CLI, writing to stderr so diagnostics don't mix
+/// with command output.
+pub fn init_cli(verbosity: u8) {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(env_filter(verbosity))
+        .init();
+}
+
+pub fn init(sink: LogSink, format: LogFormat, verbosity: u8) -> Result<()> {
+    let (writer, ansi, timestamps) = match sink {
+        LogSink::Stdout => (BoxMakeWriter::new(std::io::stdout), true, true),
+        LogSink::File => {
+            let path = crate::config::get_daemon_log_path()?;
+            let file = OpenOptions::new().create(true).append(true).open(&path).map_err(|e| {
+                RpmError::Config(format!("Failed to open daemon log file {}: {}", path.display(), e))
+            })?;
+            (BoxMakeWriter::new(Mutex::new(file)), false, true)
+        }
+        // The system log stamps entries itself.
+        LogSink::System => (BoxMakeWriter::new(system::SystemLogWriter::new()?), false, false),
+    };
+
+    let layer: Box<dyn Layer<Registry> + Send + Sync> = match format {
+        LogFormat::Text => {
+            let layer = tracing_subscriber::fmt::layer().with_writer(writer).with_ansi(ansi);
+            if timestamps {
+                layer.boxed()
+            } else {
+                layer.without_time().boxed()
+            }
+        }
+        LogFormat::Json => {
+            let layer = tracing_subscriber::fmt::layer().json().with_writer(writer).with_ansi(false);
+            if timestamps {
+                layer.boxed()
+            } else {
+                layer.without_time().boxed()
+            }
+        }
+    };
+
+    tracing_subscriber::registry()
+        .with(layer)
+        .with(env_filter(verbosity))
+        .try_init()
+        .map_err(|e| RpmError::Config(format!("Failed to initialize logging: {}", e)))
+}
+
+#[cfg(unix)]
+mod system {
+    use crate::Result;
+    use std::ffi::CString;
+    use std::io::Write;
+    use tracing::{Level, Metadata};
+    use tracing_subscriber::fmt::MakeWriter;
+
+    pub struct SystemLogWriter;
+
+    impl SystemLogWriter {
+        pub fn new() -> Result<Self> {
+            // openlog keeps the ident pointer, so it must live for the rest of the process.
+            static IDENT: &[u8] = b"rpm-daemon\0";
+            unsafe {
+                libc::openlog(IDENT.as_ptr() as *const libc::c_char, libc::LOG_PID, libc::LOG_DAEMON);
+            }
+            Ok(SystemLogWriter)
+        }
+    }
+
+    /// Collects one formatted event and hands it to syslog when dropped.
+    pub struct SyslogEntry {
+        priority: libc::c_int,
+        buffer: Vec<u8>,
+    }
+
+    impl Write for SyslogEntry {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.buffer.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Drop for SyslogEntry {
+        fn drop(&mut self) {
+            let message = String::from_utf8_lossy(&self.buffer);
+            let message = message.trim_end().replace('\0', "");
+            if message.is_empty() {
+                return;
+            }
+            if let Ok(message) = CString::new(message) {
+                unsafe {
+                    libc::syslog(self.priority, c"%s".as_ptr(), message.as_ptr());
+                }
+            }
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for SystemLogWriter {
+        type Writer = SyslogEntry;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            SyslogEntry {
+                priority: libc::LOG_INFO,
+                buffer: Vec::new(),
+            }
+        }
+
+        fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+            let priority = match *meta.level() {
+                Level::ERROR => libc::LOG_ERR,
+                Level::WARN => libc::LOG_WARNING,
+                Level::INFO => libc::LOG_INFO,
+                Level::DEBUG | Level::TRACE => libc::LOG_DEBUG,
+            };
+            SyslogEntry {
+                priority,
+                buffer: Vec::new(),
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+mod system {
+    use crate::{Result, RpmError};
+    use std::io::Write;
+    use std::ptr;
+    use tracing::{Level, Metadata};
+    use tracing_subscriber::fmt::MakeWriter;
+    use winapi::um::winbase::{RegisterEventSourceW, ReportEventW};
+    use winapi::um::winnt::{
+        EVENTLOG_ERROR_TYPE, EVENTLOG_INFORMATION_TYPE, EVENTLOG_WARNING_TYPE, HANDLE,
+    };
+
+    pub struct SystemLogWriter {
+        source: HANDLE,
+    }
+
+    // The event source handle may be used from any thread.
+    unsafe impl Send for SystemLogWriter {}
+    unsafe impl Sync for SystemLogWriter {}
+
+    impl SystemLogWriter {
+        pub fn new() -> Result<Self> {
+            let name: Vec<u16> = "RPMDaemon".encode_utf16().chain(std::iter::once(0)).collect();
+            let source = unsafe { RegisterEventSourceW(ptr::null(), name.as_ptr()) };
+            if source.is_null() {
+                return Err(RpmError::Config(format!(
+                    "Failed to register event source: {}",
+                    std::io::Error::last_os_error()
+                )));
+            }
+            Ok(SystemLogWriter { source })
+        }
+    }
+
+    /// Collects one formatted event and reports it to the Event Log when dropped.
+    pub struct EventLogEntry {
+        source: HANDLE,
+        event_type: u16,
+        buffer: Vec<u8>,
+    }
+
+    impl Write for EventLogEntry {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.buffer.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Drop for EventLogEntry {
+        fn drop(&mut self) {
+            let message = String::from_utf8_lossy(&self.buffer);
+            let message = message.trim_end();
+            if message.is_empty() {
+                return;
+            }
+            let wide: Vec<u16> = message.encode_utf16().chain(std::iter::once(0)).collect();
+            let mut strings = [wide.as_ptr()];
+            unsafe {
+                ReportEventW(
+                    self.source,
+                    self.event_type,
+                    0,
+                    0,
+                    ptr::null_mut(),
+                    1,
+                    0,
+                    strings.as_mut_ptr(),
+                    ptr::null_mut(),
+                );
+            }
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for SystemLogWriter {
+        type Writer = EventLogEntry;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            EventLogEntry {
+                source: self.source,
+                event_type: EVENTLOG_INFORMATION_TYPE,
+                buffer: Vec::new(),
+            }
+        }
+
+        fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+            let event_type = match *meta.level() {
+                Level::ERROR => EVENTLOG_ERROR_TYPE,
+                Level::WARN => EVENTLOG_WARNING_TYPE,
+                _ => EVENTLOG_INFORMATION_TYPE,
+            };
+            EventLogEntry {
+                source: self.source,
+                event_type,
+                buffer: Vec::new(),
+            }
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod system {
+    use crate::{Result, RpmError};
+
+    pub struct SystemLogWriter;
+
+    impl SystemLogWriter {
+        pub fn new() -> Result<fn() -> std::io::Stdout> {
+            Err(RpmError::Config(
+                "System logging is not supported on this platform".to_string(),
+            ))
+        }
+    }
+}
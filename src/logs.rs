@@ -0,0 +1,521 @@
+use crate::{config::Config, Result};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+const LOG_BUFFER_CAPACITY: usize = 1000;
+const LOG_CHANNEL_CAPACITY: usize = 256;
+const DEFAULT_MAX_OPEN_LOG_FILES: usize = 256;
+const DEFAULT_LOG_MAX_SIZE: u64 = 100 * 1024 * 1024;
+const DEFAULT_LOG_RETENTION_DAYS: u32 = 30;
+
+static LOG_FILES: OnceLock<Mutex<LogFilePool>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+impl LogStream {
+    fn file_suffix(&self) -> &'static str {
+        match self {
+            LogStream::Stdout => "out",
+            LogStream::Stderr => "err",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLine {
+    pub timestamp: DateTime<Utc>,
+    pub stream: LogStream,
+    pub message: String,
+}
+
+impl LogLine {
+    pub fn format(&self) -> String {
+        format!("[{}] {}", self.timestamp.format("%Y-%m-%d %H:%M:%S"), self.message)
+    }
+
+    /// Reads back a line written to a log file by `format`. A line without
+    /// the timestamp prefix keeps its full text, dated at the Unix epoch.
+    pub fn parse(text: &str, stream: LogStream) -> Self {
+        let parsed = text
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once("] "))
+            .and_then(|(timestamp, message)| {
+                chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S")
+                    .ok()
+                    .map(|timestamp| (timestamp.and_utc(), message))
+            });
+        match parsed {
+            Some((timestamp, message)) => LogLine { timestamp, stream, message: message.to_string() },
+            None => LogLine { timestamp: DateTime::UNIX_EPOCH, stream, message: text.to_string() },
+        }
+    }
+}
+
+pub type LogBuffer = Arc<Mutex<VecDeque<LogLine>>>;
+
+pub fn new_buffer() -> LogBuffer {
+    Arc::new(Mutex::new(VecDeque::with_capacity(LOG_BUFFER_CAPACITY)))
+}
+
+/// Broadcasts each line as it is read, for clients attached to the process.
+pub type LogChannel = tokio::sync::broadcast::Sender<LogLine>;
+
+pub fn new_channel() -> LogChannel {
+    tokio::sync::broadcast::channel(LOG_CHANNEL_CAPACITY).0
+}
+
+pub fn log_file_path(name: &str, stream: LogStream) -> Result<PathBuf> {
+    let logs_dir = crate::config::get_logs_dir()?;
+    Ok(logs_dir.join(format!("{}-{}.log", name, stream.file_suffix())))
+}
+
+/// Keeps a bounded set of log files open for appending. Files are opened on
+/// first write and the least recently written one is closed once the cap is
+/// reached, so the daemon's descriptor usage doesn't grow with process count.
+/// Files that grow past the size limit are rotated to gzip segments.
+pub struct LogFilePool {
+    max_open: usize,
+    max_size: u64,
+    retention_days: u32,
+    files: HashMap<PathBuf, OpenLogFile>,
+    clock: u64,
+}
+
+struct OpenLogFile {
+    file: File,
+    size: u64,
+    last_used: u64,
+}
+
+impl LogFilePool {
+    fn new(max_open: usize, max_size: u64, retention_days: u32) -> Self {
+        LogFilePool {
+            max_open: max_open.max(1),
+            max_size,
+            retention_days,
+            files: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    pub fn write_line(&mut self, path: &Path, line: &str) -> std::io::Result<()> {
+        self.clock += 1;
+
+        if !self.files.contains_key(path) {
+            if self.files.len() >= self.max_open {
+                self.evict_least_recently_used();
+            }
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            let size = file.metadata()?.len();
+            self.files.insert(path.to_path_buf(), OpenLogFile { file, size, last_used: 0 });
+        }
+
+        let entry = self.files.get_mut(path).expect("log file was just opened");
+        entry.last_used = self.clock;
+        writeln!(entry.file, "{}", line)?;
+        entry.size += line.len() as u64 + 1;
+
+        if self.max_size > 0 && entry.size >= self.max_size {
+            self.files.remove(path);
+            rotate(path, self.retention_days)?;
+        }
+        Ok(())
+    }
+
+    pub fn close(&mut self, path: &Path) {
+        self.files.remove(path);
+    }
+
+    /// Closes every open log file so the next write to each reopens it by
+    /// path. Lets an external logrotate move files without the daemon
+    /// writing on into the moved-away inode.
+    pub fn reopen_all(&mut self) {
+        self.files.clear();
+    }
+
+    /// Rotates a log file now, regardless of its size. Empty or missing
+    /// files are left alone.
+    pub fn rotate_now(&mut self, path: &Path) -> std::io::Result<()> {
+        self.files.remove(path);
+        match path.metadata() {
+            Ok(metadata) if metadata.len() > 0 => rotate(path, self.retention_days),
+            _ => Ok(()),
+        }
+    }
+
+    /// Closes a log file and deletes it along with its rotated segments.
+    /// Files that are already gone are not an error.
+    pub fn remove(&mut self, path: &Path) -> std::io::Result<()> {
+        self.files.remove(path);
+        let mut segments = vec![path.to_path_buf()];
+        let mut index = 1;
+        while compressed_path(path, index).exists() {
+            segments.push(compressed_path(path, index));
+            index += 1;
+        }
+
+        for segment in segments {
+            match std::fs::remove_file(&segment) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let oldest = self
+            .files
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(path, _)| path.clone());
+
+        if let Some(path) = oldest {
+            self.files.remove(&path);
+        }
+    }
+}
+
+/// Applies the daemon's log limits to the shared log file pool. Only the first
+/// call has an effect; writes made before any call use the default limits.
+pub fn configure_file_pool(config: &Config) {
+    let _ = LOG_FILES.set(Mutex::new(LogFilePool::new(
+        config.max_open_log_files,
+        config.log_max_size,
+        config.log_retention_days,
+    )));
+}
+
+pub fn file_pool() -> &'static Mutex<LogFilePool> {
+    LOG_FILES.get_or_init(|| {
+        Mutex::new(LogFilePool::new(
+            DEFAULT_MAX_OPEN_LOG_FILES,
+            DEFAULT_LOG_MAX_SIZE,
+            DEFAULT_LOG_RETENTION_DAYS,
+        ))
+    })
+}
+
+fn compressed_path(path: &Path, index: u32) -> PathBuf {
+    PathBuf::from(format!("{}.{}.gz", path.display(), index))
+}
+
+struct Rotation {
+    path: PathBuf,
+    pending: PathBuf,
+    retention_days: u32,
+}
+
+static ROTATIONS: OnceLock<Mutex<std::sync::mpsc::Sender<Rotation>>> = OnceLock::new();
+
+/// Moves the live file aside and queues it for compression. A single worker
+/// thread handles the queue so segments are shifted and compressed in the
+/// order they were rotated, without holding up writers on the pool lock.
+fn rotate(path: &Path, retention_days: u32) -> std::io::Result<()> {
+    let pending = PathBuf::from(format!(
+        "{}.{}.pending",
+        path.display(),
+        Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+    std::fs::rename(path, &pending)?;
+
+    let sender = ROTATIONS.get_or_init(|| {
+        let (tx, rx) = std::sync::mpsc::channel::<Rotation>();
+        std::thread::spawn(move || {
+            for rotation in rx {
+                if let Err(e) = finish_rotation(&rotation) {
+                    tracing::warn!("Failed to rotate log {}: {}", rotation.path.display(), e);
+                }
+            }
+        });
+        Mutex::new(tx)
+    });
+
+    let rotation = Rotation {
+        path: path.to_path_buf(),
+        pending,
+        retention_days,
+    };
+    sender
+        .lock()
+        .unwrap()
+        .send(rotation)
+        .map_err(|e| std::io::Error::other(e.to_string()))
+}
+
+/// Shifts existing `<log>.N.gz` segments up by one and compresses the rotated
+/// file into `<log>.1.gz`. Segments older than the retention period are removed.
+fn finish_rotation(rotation: &Rotation) -> std::io::Result<()> {
+    let path = &rotation.path;
+    let mut highest = 0;
+    while compressed_path(path, highest + 1).exists() {
+        highest += 1;
+    }
+
+    let retention = std::time::Duration::from_secs(u64::from(rotation.retention_days) * 24 * 60 * 60);
+    for index in (1..=highest).rev() {
+        let segment = compressed_path(path, index);
+        let expired = rotation.retention_days > 0
+            && segment
+                .metadata()
+                .and_then(|m| m.modified())
+                .map(|modified| modified.elapsed().unwrap_or_default() > retention)
+                .unwrap_or(false);
+
+        if expired {
+            std::fs::remove_file(&segment)?;
+        } else {
+            std::fs::rename(&segment, compressed_path(path, index + 1))?;
+        }
+    }
+
+    compress(&rotation.pending, &compressed_path(path, 1))
+}
+
+fn compress(source: &Path, target: &Path) -> std::io::Result<()> {
+    let mut input = File::open(source)?;
+    let mut encoder = GzEncoder::new(File::create(target)?, Compression::default());
+    std::io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+    std::fs::remove_file(source)
+}
+
+/// Returns up to `count` of the most recent lines written to a log file,
+/// reaching back into rotated segments (decompressing `.gz` ones) when the
+/// live file alone holds fewer lines. Lines are returned oldest first.
+pub fn read_log_file(path: &Path, count: usize) -> Result<Vec<String>> {
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut segments = vec![path.to_path_buf()];
+    let mut index = 1;
+    while compressed_path(path, index).exists() {
+        segments.push(compressed_path(path, index));
+        index += 1;
+    }
+
+    let mut lines = VecDeque::new();
+    for segment in segments {
+        if lines.len() >= count {
+            break;
+        }
+        if !segment.exists() {
+            continue;
+        }
+
+        let file = File::open(&segment)?;
+        let reader: Box<dyn Read> = if segment.extension().is_some_and(|ext| ext == "gz") {
+            Box::new(GzDecoder::new(file))
+        } else {
+            Box::new(file)
+        };
+
+        let mut segment_lines = VecDeque::new();
+        for line in std::io::BufReader::new(reader).lines() {
+            segment_lines.push_back(line?);
+            if segment_lines.len() > count - lines.len() {
+                segment_lines.pop_front();
+            }
+        }
+
+        while let Some(line) = segment_lines.pop_back() {
+            lines.push_front(line);
+        }
+    }
+
+    Ok(lines.into_iter().collect())
+}
+
+/// Receives lines from one of a child's output pipes and records them in the
+/// in-memory buffer and the process's log file for that stream, and passes
+/// them on to any attached clients.
+struct LogSink {
+    name: String,
+    stream: LogStream,
+    buffer: LogBuffer,
+    live: LogChannel,
+    path: Option<PathBuf>,
+    rate_limit: Option<RateLimit>,
+}
+
+/// Caps how many lines per second a stream records; the rest are dropped
+/// and counted, and a marker line reports how many once the second is over.
+struct RateLimit {
+    lines_per_sec: u32,
+    window_start: std::time::Instant,
+    in_window: u32,
+    suppressed: u64,
+}
+
+impl RateLimit {
+    fn new(lines_per_sec: u32) -> Self {
+        RateLimit {
+            lines_per_sec,
+            window_start: std::time::Instant::now(),
+            in_window: 0,
+            suppressed: 0,
+        }
+    }
+
+    /// Counts a line and says whether to keep it. Returns the number of lines
+    /// dropped in the previous window when this line starts a new one.
+    fn admit(&mut self) -> (bool, u64) {
+        let mut suppressed = 0;
+        if self.window_start.elapsed() >= std::time::Duration::from_secs(1) {
+            suppressed = std::mem::take(&mut self.suppressed);
+            self.window_start = std::time::Instant::now();
+            self.in_window = 0;
+        }
+        if self.in_window >= self.lines_per_sec {
+            self.suppressed += 1;
+            return (false, suppressed);
+        }
+        self.in_window += 1;
+        (true, suppressed)
+    }
+}
+
+impl LogSink {
+    fn new(
+        name: &str,
+        stream: LogStream,
+        path: Option<PathBuf>,
+        buffer: LogBuffer,
+        live: LogChannel,
+        rate_limit: Option<u32>,
+    ) -> Self {
+        if path.is_none() {
+            tracing::warn!("No {} log file for '{}'; output is only kept in memory", stream.file_suffix(), name);
+        }
+
+        LogSink {
+            name: name.to_string(),
+            stream,
+            buffer,
+            live,
+            path,
+            rate_limit: rate_limit.map(RateLimit::new),
+        }
+    }
+
+    /// Records a line from the process, subject to the rate limit.
+    fn push(&mut self, message: String) {
+        if let Some(rate_limit) = &mut self.rate_limit {
+            let (keep, suppressed) = rate_limit.admit();
+            if suppressed > 0 {
+                self.record(suppressed_marker(suppressed));
+            }
+            if !keep {
+                return;
+            }
+        }
+        self.record(message);
+    }
+
+    /// Reports lines still being held back when the stream closes.
+    fn finish(&mut self) {
+        if let Some(suppressed) = self.rate_limit.as_mut().map(|limit| std::mem::take(&mut limit.suppressed)) {
+            if suppressed > 0 {
+                self.record(suppressed_marker(suppressed));
+            }
+        }
+    }
+
+    fn record(&mut self, message: String) {
+        let line = LogLine {
+            timestamp: Utc::now(),
+            stream: self.stream,
+            message,
+        };
+
+        if let Some(path) = &self.path {
+            if let Err(e) = file_pool().lock().unwrap().write_line(path, &line.format()) {
+                tracing::warn!("Failed to write log file for '{}': {}", self.name, e);
+                self.path = None;
+            }
+        }
+
+        // Broadcast while holding the buffer lock, so a subscriber that
+        // snapshots the buffer sees each line exactly once.
+        let mut buffer = self.buffer.lock().unwrap();
+        // Sending only fails when nobody is attached.
+        let _ = self.live.send(line.clone());
+        if buffer.len() >= LOG_BUFFER_CAPACITY {
+            buffer.pop_front();
+        }
+        buffer.push_back(line);
+    }
+}
+
+fn suppressed_marker(count: u64) -> String {
+    format!("[rpm] suppressed {} lines", count)
+}
+
+/// Reads one of a child's output pipes into `path`, the in-memory buffer and
+/// live subscribers. `rate_limit` caps the lines per second that are recorded.
+pub fn spawn_reader<R>(
+    reader: R,
+    name: &str,
+    stream: LogStream,
+    path: Option<PathBuf>,
+    buffer: LogBuffer,
+    live: LogChannel,
+    rate_limit: Option<u32>,
+) where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    let mut sink = LogSink::new(name, stream, path, buffer, live, rate_limit);
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(reader).lines();
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) => sink.push(line),
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::warn!("Failed to read output of '{}': {}", sink.name, e);
+                    break;
+                }
+            }
+        }
+        sink.finish();
+    });
+}
+
+/// Reads a merged stdout/stderr pipe. The pipe comes from `std::io::pipe`, so it
+/// is drained on the blocking pool rather than through the async reactor.
+pub fn spawn_pipe_reader(
+    reader: std::io::PipeReader,
+    name: &str,
+    path: Option<PathBuf>,
+    buffer: LogBuffer,
+    live: LogChannel,
+    rate_limit: Option<u32>,
+) {
+    let mut sink = LogSink::new(name, LogStream::Stdout, path, buffer, live, rate_limit);
+    tokio::task::spawn_blocking(move || {
+        for line in std::io::BufReader::new(reader).lines() {
+            match line {
+                Ok(line) => sink.push(line),
+                Err(e) => {
+                    tracing::warn!("Failed to read output of '{}': {}", sink.name, e);
+                    break;
+                }
+            }
+        }
+        sink.finish();
+    });
+}
@@ -1,36 +1,65 @@
 use clap::Parser;
-use rpm::{cli::*, ui::*, Result};
+use rpm::ipc::{IpcRequest, IpcResponse};
+use rpm::process::ProcessEvent;
+use rpm::{cli::*, ui::*, Result, RpmError};
 use std::process;
-use tokio;
 use colored::*;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    tracing_subscriber::fmt::init();
-
     let cli = Cli::parse();
+    set_quiet(cli.quiet);
+
+    match &cli.command {
+        Commands::Daemon { log_sink, log_format, .. } => {
+            let sink = match log_sink {
+                Some(sink) => *sink,
+                None => rpm::config::Config::load().await?.daemon_log_sink,
+            };
+            let format = log_format.unwrap_or_else(rpm::logging::LogFormat::from_env);
+            rpm::logging::init(sink, format, cli.verbose)?;
+        }
+        _ => {
+            rpm::logging::init_cli(cli.verbose);
+            let cpu_mode = match cli.cpu_mode {
+                Some(mode) => mode,
+                None => rpm::config::Config::load().await.map(|config| config.cpu_mode).unwrap_or_default(),
+            };
+            set_cpu_mode(cpu_mode);
+        }
+    }
 
     let result = match cli.command {
-        Commands::Start {
-            command,
-            name,
-            cwd,
-            instances,
-            autorestart,
-            max_memory,
-            env,
-        } => {
-            let config = ProcessConfig::from_args(command, name, cwd, instances, autorestart, max_memory, env)?;
-            handle_start(config).await
-        }
-        Commands::Stop { name } => handle_stop(name).await,
-        Commands::Restart { name } => handle_restart(name).await,
-        Commands::Delete { name } => handle_delete(name).await,
-        Commands::List => handle_list().await,
-        Commands::Logs { name, lines, follow } => handle_logs(name, lines, follow).await,
-        Commands::Show { name } => handle_show(name).await,
-        Commands::Monitor => handle_monitor().await,
-        Commands::Daemon { foreground } => {
+        Commands::Start(args) => {
+            if let Some(path) = args.command.clone().filter(|command| rpm::ecosystem::is_ecosystem_file(command)) {
+                handle_start_ecosystem(path, args.profile).await
+            } else {
+                let force = args.force;
+                match ProcessConfig::from_args(*args) {
+                    Ok(config) => handle_start(config, force).await,
+                    Err(e) => Err(e),
+                }
+            }
+        }
+        Commands::Try(args) => match ProcessConfig::from_args(*args) {
+            Ok(config) => handle_try(config).await,
+            Err(e) => Err(e),
+        },
+        Commands::Stop { names, timeout } => handle_stop(names, timeout).await,
+        Commands::Restart { names, update_env, timeout, group, rolling, rolling_wait } => match group {
+            Some(group) if rolling => handle_rolling_restart(group, update_env, timeout, rolling_wait).await,
+            Some(group) => handle_group_restart(group, update_env, timeout).await,
+            None => handle_restart(names, update_env, timeout).await,
+        },
+        Commands::Delete { names, yes } => handle_delete(names, yes).await,
+        Commands::List { sort, show_desc, format } => handle_list(sort, show_desc, format).await,
+        Commands::Top => handle_top().await,
+        Commands::Logs { name, lines, follow, json } => handle_logs(name, lines, follow, json).await,
+        Commands::Events { json } => handle_events(json).await,
+        Commands::DaemonLogs { lines, follow } => handle_daemon_logs(lines, follow).await,
+        Commands::Show { name, json } => handle_show(name, json, cli.local).await,
+        Commands::Monitor => handle_monitor(cli.local).await,
+        Commands::Daemon { foreground, .. } => {
             #[cfg(windows)]
             {
                 if !foreground {
@@ -41,121 +70,491 @@ async fn main() -> Result<()> {
             }
             handle_daemon(foreground).await
         },
-        Commands::Kill => handle_kill().await,
-        Commands::Reload { name } => handle_reload(name).await,
+        Commands::Startup { user, restart, restart_sec, log_dir, dry_run } => {
+            handle_startup(rpm::daemon::ServiceOptions { user, restart, restart_sec, log_dir, dry_run }).await
+        }
+        Commands::Kill { yes } => handle_kill(yes).await,
+        Commands::KillProcess { names, yes } => handle_kill_process(names, yes).await,
+        Commands::Update { name, env, append_env: _, replace_env } => handle_update(name, env, replace_env).await,
+        Commands::Reload { name } => {
+            if rpm::ecosystem::is_ecosystem_file(&name) {
+                handle_apply_ecosystem(name).await
+            } else {
+                handle_reload(name).await
+            }
+        }
+        Commands::Enable { name } => handle_set_autostart(name, true).await,
+        Commands::Disable { name } => handle_set_autostart(name, false).await,
+        Commands::Pause { name } => handle_set_monitored(name, false).await,
+        Commands::Resume { name } => handle_set_monitored(name, true).await,
+        Commands::Send { name, text } => handle_send(name, text).await,
+        Commands::ReloadLogs { rotate } => handle_reload_logs(rotate).await,
+        Commands::SetInterval { secs } => handle_set_interval(secs).await,
+        Commands::Attach { name } => handle_attach(name).await,
         Commands::Save => handle_save().await,
         Commands::Resurrect => handle_resurrect().await,
-        Commands::Status => handle_status().await,
+        Commands::Status { fail_on } => handle_status(fail_on).await,
+        Commands::IsRunning { name } => handle_is_running(name).await,
+        Commands::Info => handle_info(cli.local).await,
+        Commands::Man => handle_man(),
     };
 
     if let Err(e) = result {
         print_error(&format!("Error: {}", e));
-        process::exit(1);
+        process::exit(e.exit_code());
     }
 
     Ok(())
 }
 
-async fn handle_start(config: ProcessConfig) -> Result<()> {
-    let spinner = ProgressIndicator::show_spinner(&format!("Starting process '{}'", config.name));
+async fn handle_start(config: ProcessConfig, force: bool) -> Result<()> {
     let client = rpm::ipc::IpcClient::new().await?;
-    client.start_process(config).await?;
+    if !force {
+        warn_if_already_running(&client, &config).await;
+    }
+
+    let spinner = ProgressIndicator::show_spinner(&format!("Starting process '{}'", config.name));
+    let started = client.start_process(config).await;
     spinner.finish_and_clear();
-    print_success("Process started successfully");
+    let started = started?;
+    let pid = started.pid.map_or_else(|| "unknown".to_string(), |pid| pid.to_string());
+    print_success(&format!("Process '{}' started (id {}, pid {})", started.id, started.numeric_id, pid));
     Ok(())
 }
 
-async fn handle_stop(name: String) -> Result<()> {
-    let spinner = ProgressIndicator::show_spinner(&format!("Stopping process '{}'", name));
+/// Warns when the same command is already running under another name, which
+/// is usually a double start rather than a deliberate second copy.
+async fn warn_if_already_running(client: &rpm::ipc::IpcClient, config: &ProcessConfig) {
+    let Ok(processes) = client.list_processes().await else {
+        return;
+    };
+    let command: Vec<&str> = config.command.split_whitespace().collect();
+
+    let existing = processes.iter().find(|process| {
+        process.status == rpm::process::ProcessStatus::Running
+            && process.name != config.name
+            && process.command.split_whitespace().eq(command.iter().copied())
+    });
+    if let Some(existing) = existing {
+        print_warning(&format!(
+            "'{}' is already running the same command; starting a second copy (use --force to skip this check)",
+            existing.name
+        ));
+    }
+}
+
+async fn handle_start_ecosystem(path: String, profile: Option<String>) -> Result<()> {
+    let configs = rpm::ecosystem::load(std::path::Path::new(&path))?;
+    let targets = configs
+        .into_iter()
+        .map(|mut config| {
+            if profile.is_some() {
+                config.profile = profile.clone();
+            }
+            (config.name.clone(), IpcRequest::StartProcess(Box::new(config)))
+        })
+        .collect();
     let client = rpm::ipc::IpcClient::new().await?;
-    client.stop_process(&name).await?;
-    spinner.finish_and_clear();
-    print_success(&format!("Process '{}' stopped", name));
-    Ok(())
+    handle_batch(&client, ("Starting", "start", "started"), targets).await
 }
 
-async fn handle_restart(name: String) -> Result<()> {
-    let spinner = ProgressIndicator::show_spinner(&format!("Restarting process '{}'", name));
+async fn handle_stop(names: Vec<String>, timeout: Option<u64>) -> Result<()> {
     let client = rpm::ipc::IpcClient::new().await?;
-    client.restart_process(&name).await?;
+    let targets = expand_targets(&client, names)
+        .await?
+        .into_iter()
+        .map(|name| (name.clone(), IpcRequest::StopProcess { name, timeout }))
+        .collect();
+    handle_batch(&client, ("Stopping", "stop", "stopped"), targets).await
+}
+
+async fn handle_restart(names: Vec<String>, update_env: bool, timeout: Option<u64>) -> Result<()> {
+    let client = rpm::ipc::IpcClient::new().await?;
+    let targets = expand_targets(&client, names)
+        .await?
+        .into_iter()
+        .map(|name| (name.clone(), IpcRequest::RestartProcess { name, update_env, timeout }))
+        .collect();
+    handle_batch(&client, ("Restarting", "restart", "restarted"), targets).await
+}
+
+async fn handle_kill_process(names: Vec<String>, yes: bool) -> Result<()> {
+    let client = rpm::ipc::IpcClient::new().await?;
+    let names = expand_targets(&client, names).await?;
+    if !yes && !confirm_targets("Kill", &names) {
+        return Ok(());
+    }
+    let targets = names
+        .into_iter()
+        .map(|name| (name.clone(), IpcRequest::ForceKill(name)))
+        .collect();
+    handle_batch(&client, ("Killing", "kill", "killed"), targets).await
+}
+
+async fn handle_group_restart(group: String, update_env: bool, timeout: Option<u64>) -> Result<()> {
+    let client = rpm::ipc::IpcClient::new().await?;
+    let targets: Vec<(String, IpcRequest)> = client
+        .list_processes()
+        .await?
+        .into_iter()
+        .filter(|p| p.config.group.as_deref() == Some(group.as_str()))
+        .map(|p| (p.name.clone(), IpcRequest::RestartProcess { name: p.name, update_env, timeout }))
+        .collect();
+    if targets.is_empty() {
+        return Err(RpmError::ProcessNotFound(format!("no processes in group '{}'", group)));
+    }
+    handle_batch(&client, ("Restarting", "restart", "restarted"), targets).await
+}
+
+async fn handle_rolling_restart(group: String, update_env: bool, timeout: Option<u64>, wait: u64) -> Result<()> {
+    let client = rpm::ipc::IpcClient::new().await?;
+    let spinner = ProgressIndicator::show_spinner(&format!("Rolling restart of group '{}'", group));
+    let result = client.rolling_restart(&group, update_env, timeout, wait).await;
     spinner.finish_and_clear();
-    print_success(&format!("Process '{}' restarted", name));
+    print_success(&result?);
     Ok(())
 }
 
-async fn handle_delete(name: String) -> Result<()> {
-    let spinner = ProgressIndicator::show_spinner(&format!("Deleting process '{}'", name));
+async fn handle_delete(names: Vec<String>, yes: bool) -> Result<()> {
     let client = rpm::ipc::IpcClient::new().await?;
-    client.delete_process(&name).await?;
+    let names = expand_targets(&client, names).await?;
+    if !yes && !confirm_targets("Delete", &names) {
+        return Ok(());
+    }
+    let targets = names
+        .into_iter()
+        .map(|name| (name.clone(), IpcRequest::DeleteProcess(name)))
+        .collect();
+    handle_batch(&client, ("Deleting", "delete", "deleted"), targets).await
+}
+
+/// Asks before a destructive batch, listing what it will touch. Nothing to
+/// confirm when the batch is empty; handle_batch reports that itself.
+fn confirm_targets(action: &str, names: &[String]) -> bool {
+    if names.is_empty() {
+        return true;
+    }
+    let question = match names {
+        [name] => format!("{} process '{}'?", action, name),
+        _ => format!("{} {} processes ({})?", action, names.len(), names.join(", ")),
+    };
+    if confirm(&question) {
+        return true;
+    }
+    print_warning("Aborted");
+    false
+}
+
+/// Expands `all` and wildcard patterns (`api-*`) against the daemon's process
+/// list. Plain names are passed through untouched so the daemon reports any
+/// that don't exist.
+async fn expand_targets(client: &rpm::ipc::IpcClient, names: Vec<String>) -> Result<Vec<String>> {
+    if !names.iter().any(|name| name == "all" || rpm::pattern::is_pattern(name)) {
+        return Ok(names);
+    }
+
+    let mut known: Vec<String> = client.list_processes().await?.into_iter().map(|p| p.name).collect();
+    known.sort();
+
+    let mut targets: Vec<String> = Vec::new();
+    for name in names {
+        let matches: Vec<String> = if name == "all" {
+            known.clone()
+        } else if rpm::pattern::is_pattern(&name) {
+            known
+                .iter()
+                .filter(|candidate| rpm::pattern::wildcard_match(&name, candidate))
+                .cloned()
+                .collect()
+        } else {
+            vec![name.clone()]
+        };
+
+        if matches.is_empty() && name != "all" {
+            return Err(RpmError::ProcessNotFound(name));
+        }
+        for target in matches {
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+    }
+    Ok(targets)
+}
+
+/// Sends one request per target in a single batch. A single target reports
+/// like a plain command; several are summarized in one line such as
+/// "Restarted 4, failed 1 (api: ...)". Every target is attempted, and the
+/// command fails afterwards if any of them did.
+async fn handle_batch(
+    client: &rpm::ipc::IpcClient,
+    (doing, action, done): (&str, &str, &str),
+    targets: Vec<(String, IpcRequest)>,
+) -> Result<()> {
+    let (names, requests): (Vec<String>, Vec<IpcRequest>) = targets.into_iter().unzip();
+    let label = match names.as_slice() {
+        [] => {
+            print_info(&format!("No processes to {}", action));
+            return Ok(());
+        }
+        [name] => format!("process '{}'", name),
+        _ => format!("{} processes", names.len()),
+    };
+
+    let spinner = ProgressIndicator::show_spinner(&format!("{} {}", doing, label));
+    let responses = client.batch(requests).await;
     spinner.finish_and_clear();
-    print_success(&format!("Process '{}' deleted", name));
-    Ok(())
+
+    let mut succeeded = 0;
+    let mut failures = Vec::new();
+    for (name, response) in names.iter().zip(responses?) {
+        match response {
+            IpcResponse::Error(e) if names.len() == 1 => return Err(e.into()),
+            IpcResponse::Error(e) => failures.push((name.clone(), RpmError::from(e))),
+            _ if names.len() == 1 => print_success(&format!("Process '{}' {}", name, done)),
+            _ => succeeded += 1,
+        }
+    }
+    if names.len() == 1 {
+        return Ok(());
+    }
+
+    if failures.is_empty() {
+        let mut done = done.to_string();
+        done[..1].make_ascii_uppercase();
+        print_success(&format!("{} {}", done, succeeded));
+        return Ok(());
+    }
+    Err(batch_error(failures, format!("{} {} of {}", done, succeeded, names.len())))
 }
 
-async fn handle_list() -> Result<()> {
+/// One error for the failed operations of a batch, e.g. "Process not found:
+/// api, web (stopped 2 of 4)". It keeps the variant all failures share, so
+/// the exit code still tells what went wrong, and is a `Process` error when
+/// they differ.
+fn batch_error(failures: Vec<(String, RpmError)>, outcome: String) -> RpmError {
+    let kind = failures[0].1.kind();
+    let shared = failures.iter().all(|(_, e)| e.kind() == kind);
+    let details: Vec<String> = failures
+        .iter()
+        .map(|(name, e)| {
+            let detail = if shared { e.detail() } else { e.to_string() };
+            if detail == *name { detail } else { format!("{}: {}", name, detail) }
+        })
+        .collect();
+    let message = format!("{} ({})", details.join("; "), outcome);
+    if shared {
+        RpmError::from_kind(kind, message)
+    } else {
+        RpmError::Process(message)
+    }
+}
+
+async fn handle_list(sort: Option<SortKey>, show_desc: bool, format: Option<String>) -> Result<()> {
     let client = rpm::ipc::IpcClient::new().await?;
-    let processes = client.list_processes().await?;
-    
+    if let Some(format) = format {
+        // Templates can reach any field, so these need the full info.
+        let mut processes = client.list_processes().await?;
+        if let Some(key) = sort {
+            sort_processes(&mut processes, key);
+        }
+        if format == "json" {
+            println!("{}", serde_json::to_string_pretty(&processes)?);
+        } else {
+            for process in &processes {
+                println!("{}", rpm::template::render(&format, &serde_json::to_value(process)?)?);
+            }
+        }
+        return Ok(());
+    }
+
+    let mut processes = client.list_summaries().await?;
+    if let Some(key) = sort {
+        sort_processes(&mut processes, key);
+    }
+
     print_header("Process List");
     let process_refs: Vec<&_> = processes.iter().collect();
-    println!("{}", TableFormatter::format_process_list(&process_refs));
+    println!("{}", TableFormatter::format_process_list(&process_refs, show_desc, None));
     
     Ok(())
 }
 
-async fn handle_logs(name: String, lines: usize, follow: bool) -> Result<()> {
+async fn handle_top() -> Result<()> {
+    let client = rpm::ipc::IpcClient::new().await?;
+    let mut processes = client.list_processes().await?;
+    sort_processes(&mut processes, SortKey::Cpu);
+
+    let process_refs: Vec<&_> = processes.iter().collect();
+    println!("{}", TableFormatter::format_resource_snapshot(&process_refs));
+    Ok(())
+}
+
+async fn handle_logs(name: String, lines: usize, follow: bool, json: bool) -> Result<()> {
     let client = rpm::ipc::IpcClient::new().await?;
+    let print_line = |line: &rpm::logs::LogLine| {
+        if json {
+            #[derive(serde::Serialize)]
+            struct JsonLine<'a> {
+                ts: chrono::DateTime<chrono::Utc>,
+                stream: &'static str,
+                msg: &'a str,
+            }
+            let stream = match line.stream {
+                rpm::logs::LogStream::Stdout => "stdout",
+                rpm::logs::LogStream::Stderr => "stderr",
+            };
+            let json_line = JsonLine { ts: line.timestamp, stream, msg: &line.message };
+            println!("{}", serde_json::to_string(&json_line).unwrap_or_default());
+        } else {
+            println!("{}", format_log_line(&line.format()));
+        }
+    };
     
     if follow {
-        print_header(&format!("Following logs for '{}'", name));
-        print_info("Press Ctrl+C to exit");
-        println!();
-        
-        // Get initial logs
-        let initial_logs = client.get_logs(&name, lines, false).await?;
-        for log in initial_logs {
-            println!("{}", format_log_line(&log));
+        if !json {
+            print_header(&format!("Following logs for '{}'", name));
+            print_info("Press Ctrl+C to exit");
+            print_blank();
         }
         
-        // Follow new logs
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(500));
-        let mut last_log_count = lines;
-        
-        loop {
-            interval.tick().await;
-            
-            match client.get_logs(&name, last_log_count + 50, false).await {
-                Ok(logs) => {
-                    if logs.len() > last_log_count {
-                        for log in logs.iter().skip(last_log_count) {
-                            println!("{}", format_log_line(log));
-                        }
-                        last_log_count = logs.len();
-                    }
-                }
-                Err(e) => {
-                    print_error(&format!("Error following logs: {}", e));
-                    break;
-                }
-            }
+        // The backlog and the live lines come over one stream, so nothing is
+        // dropped or repeated in between. `--lines 0` shows only new output.
+        let mut subscription = client.attach_logs(&name, lines).await?;
+        while let Some(line) = subscription.next_line().await? {
+            print_line(&line);
+        }
+        if !json {
+            print_warning(&format!("Process '{}' is no longer available", name));
         }
     } else {
+        if lines > rpm::process::MAX_LOG_LINES && !json {
+            print_warning(&format!(
+                "Only the last {} lines can be fetched at once",
+                rpm::process::MAX_LOG_LINES
+            ));
+        }
         let logs = client.get_logs(&name, lines, false).await?;
         
         if logs.is_empty() {
-            print_warning(&format!("No logs found for process '{}'", name));
+            if !json {
+                print_warning(&format!("No logs found for process '{}'", name));
+            }
             return Ok(());
         }
         
-        print_header(&format!("Logs for '{}' (last {} lines)", name, lines));
-        for log in logs {
-            println!("{}", format_log_line(&log));
+        if !json {
+            print_header(&format!("Logs for '{}' (last {} lines)", name, logs.len()));
+        }
+        for log in &logs {
+            print_line(log);
         }
     }
     
     Ok(())
 }
 
+async fn handle_daemon_logs(lines: usize, follow: bool) -> Result<()> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let path = rpm::config::get_daemon_log_path()?;
+    if !path.exists() {
+        return Err(RpmError::Config(format!(
+            "No daemon log at {}. Set \"daemon_log_sink\": \"file\" in config.json (or start the daemon with --log-sink file); with the default stdout sink under systemd, use journalctl -u rpm-daemon",
+            path.display()
+        )));
+    }
+
+    for line in rpm::logs::read_log_file(&path, lines)? {
+        println!("{}", line);
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    let mut position = std::fs::metadata(&path)?.len();
+    loop {
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+        // Missing for a moment while being replaced; pick it up again next tick.
+        let Ok(metadata) = std::fs::metadata(&path) else { continue };
+        if metadata.len() < position {
+            // Truncated or replaced: start over from the top.
+            position = 0;
+        }
+        if metadata.len() == position {
+            continue;
+        }
+
+        let mut file = std::fs::File::open(&path)?;
+        file.seek(SeekFrom::Start(position))?;
+        let mut chunk = Vec::new();
+        file.read_to_end(&mut chunk)?;
+        position += chunk.len() as u64;
+        print!("{}", String::from_utf8_lossy(&chunk));
+    }
+}
+
+async fn handle_events(json: bool) -> Result<()> {
+    let client = rpm::ipc::IpcClient::new().await?;
+    let mut subscription = client.subscribe_events().await?;
+    if !json {
+        print_header("Process events");
+        print_info("Press Ctrl+C to exit");
+        print_blank();
+    }
+
+    while let Some(event) = subscription.next_event().await? {
+        if json {
+            println!("{}", serde_json::to_string(&event)?);
+            continue;
+        }
+        match event {
+            ProcessEvent::Crash(event) => {
+                let exit = match event.exit_code {
+                    Some(code) => format!("exit code {}", code),
+                    None => "killed by signal".to_string(),
+                };
+                println!(
+                    "[{}] {} (id {}) exited with {} after {}s, {} restarts, {} crashes{}",
+                    event.at.format("%Y-%m-%d %H:%M:%S"),
+                    event.name.bright_white().bold(),
+                    event.id,
+                    exit,
+                    event.uptime_secs,
+                    event.restarts,
+                    event.crashes,
+                    if event.crash_looped { ", crash-looped".bright_magenta().to_string() } else { String::new() }
+                );
+            }
+            ProcessEvent::Threshold(event) => {
+                let unit = match event.resource {
+                    rpm::process::Resource::Memory => "MB",
+                    rpm::process::Resource::Cpu => "%",
+                };
+                let change = if event.exceeded {
+                    "over its warning threshold".bright_yellow()
+                } else {
+                    "back under its warning threshold".bright_green()
+                };
+                println!(
+                    "[{}] {} (id {}) {} at {:.1}{}, {} of {}{}",
+                    event.at.format("%Y-%m-%d %H:%M:%S"),
+                    event.name.bright_white().bold(),
+                    event.id,
+                    event.resource,
+                    event.usage,
+                    unit,
+                    change,
+                    event.threshold,
+                    unit
+                );
+            }
+        }
+    }
+    print_warning("The daemon ended the event stream");
+    Ok(())
+}
+
 fn format_log_line(log: &str) -> String {
     // Try to parse timestamp and format the log line with colors
     if let Some(timestamp_end) = log.find(']') {
@@ -188,36 +587,52 @@ fn format_log_line(log: &str) -> String {
     }
 }
 
-async fn handle_show(name: String) -> Result<()> {
+async fn handle_show(name: String, json: bool, local: bool) -> Result<()> {
     let client = rpm::ipc::IpcClient::new().await?;
     let info = client.get_process_info(&name).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
     
-    println!("{}", TableFormatter::format_process_details(&info));
+    println!("{}", TableFormatter::format_process_details(&info, local));
     
     Ok(())
 }
 
-async fn handle_monitor() -> Result<()> {
+async fn handle_monitor(local: bool) -> Result<()> {
     let client = rpm::ipc::IpcClient::new().await?;
     
     print_header("Process Monitor");
     print_info("Press Ctrl+C to exit");
-    println!();
+    print_blank();
     
-    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+    let refresh = tokio::time::Duration::from_secs(2);
+    let mut interval = tokio::time::interval(refresh);
+    // Restarts after the previous refresh are flagged; the first screen
+    // looks back one refresh interval.
+    let mut last_refresh = chrono::Utc::now() - chrono::Duration::from_std(refresh).unwrap_or_default();
     loop {
         interval.tick().await;
+        let fetched_at = chrono::Utc::now();
         
-        match client.list_processes().await {
+        match client.list_summaries().await {
             Ok(processes) => {
                 // Clear screen
                 print!("\x1B[2J\x1B[1;1H");
                 print_header("Process Monitor");
-                println!("{}", format!("Last updated: {}", chrono::Utc::now().format("%H:%M:%S UTC")).bright_black());
+                let now = chrono::Utc::now();
+                let updated = if local {
+                    now.with_timezone(&chrono::Local).format("%H:%M:%S %Z").to_string()
+                } else {
+                    now.format("%H:%M:%S UTC").to_string()
+                };
+                println!("{}", format!("Last updated: {}", updated).bright_black());
                 println!();
                 
                 let process_refs: Vec<&_> = processes.iter().collect();
-                println!("{}", TableFormatter::format_process_list(&process_refs));
+                println!("{}", TableFormatter::format_process_list(&process_refs, false, Some(last_refresh)));
+                last_refresh = fetched_at;
                 
                 if processes.is_empty() {
                     println!();
@@ -238,7 +653,7 @@ async fn handle_daemon(foreground: bool) -> Result<()> {
     if foreground {
         print_header("RPM Process Manager");
         print_info("Starting daemon in foreground mode...");
-        println!();
+        print_blank();
     } else {
         print_info("Starting RPM daemon in background...");
     }
@@ -246,10 +661,28 @@ async fn handle_daemon(foreground: bool) -> Result<()> {
     Ok(())
 }
 
-async fn handle_kill() -> Result<()> {
+async fn handle_startup(options: rpm::daemon::ServiceOptions) -> Result<()> {
+    if !options.dry_run {
+        print_info("Installing RPM daemon as a startup service...");
+    }
+    rpm::daemon::install_startup(&options).await?;
+    Ok(())
+}
+
+async fn handle_kill(yes: bool) -> Result<()> {
+    if !yes && !confirm("Stop the RPM daemon?") {
+        print_warning("Aborted");
+        return Ok(());
+    }
     let spinner = ProgressIndicator::show_spinner("Stopping daemon");
     let client = rpm::ipc::IpcClient::new().await?;
     client.kill_daemon().await?;
+    // The daemon removes its PID file as the last step of shutting down.
+    let pid_path = rpm::config::get_daemon_pid_path()?;
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+    while pid_path.exists() && tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
     spinner.finish_and_clear();
     print_success("Daemon stopped");
     Ok(())
@@ -264,6 +697,226 @@ async fn handle_reload(name: String) -> Result<()> {
     Ok(())
 }
 
+/// Converges the daemon on an ecosystem file: starts definitions that aren't
+/// running, updates and restarts changed ones, and deletes processes that
+/// came from the file (or one it includes) but are no longer defined in it.
+/// Unchanged processes are left alone. Everything goes out in one batch.
+async fn handle_apply_ecosystem(path: String) -> Result<()> {
+    let configs = rpm::ecosystem::load(std::path::Path::new(&path))?;
+    let mut sources: std::collections::HashSet<String> =
+        configs.iter().filter_map(|config| config.source.clone()).collect();
+    if let Ok(top) = std::path::Path::new(&path).canonicalize() {
+        sources.insert(top.display().to_string());
+    }
+
+    let client = rpm::ipc::IpcClient::new().await?;
+    let running = client.list_processes().await?;
+
+    let mut actions: Vec<(String, &str, IpcRequest)> = Vec::new();
+    for process in &running {
+        let from_file = process.config.source.as_ref().is_some_and(|source| sources.contains(source));
+        if from_file && !configs.iter().any(|config| config.name == process.name) {
+            actions.push((process.name.clone(), "deleted", IpcRequest::DeleteProcess(process.name.clone())));
+        }
+    }
+    let mut unchanged = 0;
+    for config in configs {
+        match running.iter().find(|process| process.name == config.name) {
+            None => actions.push((config.name.clone(), "started", IpcRequest::StartProcess(Box::new(config)))),
+            Some(process) if serde_json::to_value(&process.config)? != serde_json::to_value(&config)? => {
+                actions.push((config.name.clone(), "updated", IpcRequest::UpdateProcess(Box::new(config))))
+            }
+            Some(_) => unchanged += 1,
+        }
+    }
+
+    if actions.is_empty() {
+        print_success(&format!("Nothing to change, {} processes up to date", unchanged));
+        return Ok(());
+    }
+
+    let spinner = ProgressIndicator::show_spinner(&format!("Applying {}", path));
+    let (labels, requests): (Vec<(String, &str)>, Vec<IpcRequest>) =
+        actions.into_iter().map(|(name, done, request)| ((name, done), request)).unzip();
+    let responses = client.batch(requests).await;
+    spinner.finish_and_clear();
+
+    let mut failures = Vec::new();
+    for ((name, done), response) in labels.iter().zip(responses?) {
+        match response {
+            IpcResponse::Error(e) => failures.push((name.clone(), RpmError::from(e))),
+            _ => print_success(&format!("Process '{}' {}", name, done)),
+        }
+    }
+    if unchanged > 0 {
+        print_info(&format!("{} processes unchanged", unchanged));
+    }
+    if !failures.is_empty() {
+        let applied = labels.len() - failures.len();
+        return Err(batch_error(failures, format!("applied {} of {} changes", applied, labels.len())));
+    }
+    Ok(())
+}
+
+/// Appending is the default so that setting one variable never silently
+/// drops the others.
+async fn handle_update(name: String, env: Vec<String>, replace_env: bool) -> Result<()> {
+    let vars = parse_env(env)?;
+    let client = rpm::ipc::IpcClient::new().await?;
+    let spinner = ProgressIndicator::show_spinner(&format!("Updating process '{}'", name));
+    let updated = client.update_env(&name, vars, replace_env).await;
+    spinner.finish_and_clear();
+    print_success(&updated?);
+    Ok(())
+}
+
+async fn handle_set_autostart(name: String, enabled: bool) -> Result<()> {
+    let client = rpm::ipc::IpcClient::new().await?;
+    client.set_autostart(&name, enabled).await?;
+    if enabled {
+        print_success(&format!("Process '{}' will start on daemon boot", name));
+    } else {
+        print_success(&format!("Process '{}' will no longer start on daemon boot", name));
+    }
+    Ok(())
+}
+
+async fn handle_set_monitored(name: String, monitored: bool) -> Result<()> {
+    let client = rpm::ipc::IpcClient::new().await?;
+    client.set_monitored(&name, monitored).await?;
+    if monitored {
+        print_success(&format!("Monitoring resumed for '{}'", name));
+    } else {
+        print_success(&format!("Monitoring paused for '{}'", name));
+        print_info(&format!("It won't be restarted or memory-guarded until `rpm resume {}`", name));
+    }
+    Ok(())
+}
+
+async fn handle_send(name: String, text: String) -> Result<()> {
+    let client = rpm::ipc::IpcClient::new().await?;
+    client.send_input(&name, &text).await?;
+    print_success(&format!("Sent input to '{}'", name));
+    Ok(())
+}
+
+async fn handle_reload_logs(rotate: bool) -> Result<()> {
+    let client = rpm::ipc::IpcClient::new().await?;
+    client.reload_logs(rotate).await?;
+    if rotate {
+        print_success("Rotated and reopened all log files");
+    } else {
+        print_success("Reopened all log files");
+    }
+    Ok(())
+}
+
+async fn handle_set_interval(secs: u64) -> Result<()> {
+    let client = rpm::ipc::IpcClient::new().await?;
+    client.set_monitor_interval(secs).await?;
+    print_success(&format!("Daemon will check processes every {}s", secs));
+    Ok(())
+}
+
+/// Runs a process right here, through the same `ManagedProcess` the daemon
+/// uses, and prints its output until it exits or Ctrl+C stops it. The daemon
+/// never hears of it and nothing is saved. It runs once: restart policies and
+/// `--watch` don't apply. Output only goes to the terminal; no log files are
+/// written.
+async fn handle_try(config: ProcessConfig) -> Result<()> {
+    let name = config.name.clone();
+    let mut process = rpm::process::ManagedProcess::new(0, config);
+    process.disable_log_files();
+    let mut output = process.log_channel.subscribe();
+    process.start().await?;
+    print_info(&format!(
+        "Running '{}' (pid {}) without saving it. Press Ctrl+C to stop",
+        name,
+        process.info.pid.map_or("unknown".to_string(), |pid| pid.to_string())
+    ));
+
+    let mut ticker = tokio::time::interval(tokio::time::Duration::from_millis(200));
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    loop {
+        tokio::select! {
+            line = output.recv() => {
+                if let Ok(line) = line {
+                    println!("{}", format_log_line(&line.format()));
+                }
+            }
+            _ = ticker.tick() => {
+                process.check_status(std::time::Duration::ZERO).await?;
+                if process.info.status != rpm::process::ProcessStatus::Running {
+                    break;
+                }
+            }
+            _ = &mut ctrl_c => {
+                process.stop().await?;
+                print_blank();
+                print_info(&format!("Stopped '{}'", name));
+                return Ok(());
+            }
+        }
+    }
+
+    // The pipe readers may still hold the last lines.
+    while let Ok(Ok(line)) = tokio::time::timeout(tokio::time::Duration::from_millis(200), output.recv()).await {
+        println!("{}", format_log_line(&line.format()));
+    }
+    match process.last_exit_code {
+        Some(0) => {
+            print_success(&format!("'{}' exited with code 0", name));
+            Ok(())
+        }
+        Some(code) => Err(RpmError::Process(format!("'{}' exited with code {}", name, code))),
+        None => Err(RpmError::Process(format!("'{}' was killed by a signal", name))),
+    }
+}
+
+/// Prints the process's output as it arrives until Ctrl+C. When the process
+/// was started with `--stdin`, lines typed here are forwarded to it.
+async fn handle_attach(name: String) -> Result<()> {
+    use tokio::io::AsyncBufReadExt;
+
+    let client = rpm::ipc::IpcClient::new().await?;
+    let info = client.get_process_info(&name).await?;
+    let mut subscription = client.attach_logs(&name, 0).await?;
+
+    print_header(&format!("Attached to '{}'", name));
+    if info.config.stdin {
+        print_info("Input is forwarded to the process. Press Ctrl+C to detach");
+    } else {
+        print_info("Press Ctrl+C to detach");
+    }
+    print_blank();
+
+    let mut input = tokio::io::BufReader::new(tokio::io::stdin()).lines();
+    let mut forward_input = info.config.stdin;
+
+    loop {
+        tokio::select! {
+            line = subscription.next_line() => match line? {
+                Some(line) => println!("{}", format_log_line(&line.format())),
+                None => {
+                    print_warning(&format!("Process '{}' is no longer available", name));
+                    return Ok(());
+                }
+            },
+            line = input.next_line(), if forward_input => match line? {
+                Some(line) => client.send_input(&name, &line).await?,
+                None => forward_input = false,
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    subscription.detach().await?;
+    print_blank();
+    print_info(&format!("Detached from '{}'", name));
+    Ok(())
+}
+
 async fn handle_save() -> Result<()> {
     let spinner = ProgressIndicator::show_spinner("Saving process list");
     let client = rpm::ipc::IpcClient::new().await?;
@@ -276,46 +929,103 @@ async fn handle_save() -> Result<()> {
 async fn handle_resurrect() -> Result<()> {
     let spinner = ProgressIndicator::show_spinner("Resurrecting processes");
     let client = rpm::ipc::IpcClient::new().await?;
-    client.resurrect_processes().await?;
+    let message = client.resurrect_processes().await?;
     spinner.finish_and_clear();
-    print_success("Processes resurrected");
+    print_success(&message);
     Ok(())
 }
 
-async fn handle_status() -> Result<()> {
-    match rpm::ipc::IpcClient::new().await {
-        Ok(client) => {
-            match client.list_processes().await {
-                Ok(processes) => {
-                    print_header("RPM Daemon Status");
-                    print_success("Daemon is running");
-                    
-                    let running = processes.iter().filter(|p| p.status == rpm::process::ProcessStatus::Running).count();
-                    let stopped = processes.iter().filter(|p| p.status == rpm::process::ProcessStatus::Stopped).count();
-                    let errored = processes.iter().filter(|p| p.status == rpm::process::ProcessStatus::Errored).count();
-                    
-                    println!();
-                    println!("{:<20} {}", "Total processes:".bright_white(), processes.len().to_string().bright_yellow());
-                    println!("{:<20} {}", "Running:".bright_white(), running.to_string().bright_green());
-                    println!("{:<20} {}", "Stopped:".bright_white(), stopped.to_string().bright_red());
-                    println!("{:<20} {}", "Errored:".bright_white(), errored.to_string().bright_red());
-                    
-                    if !processes.is_empty() {
-                        println!();
-                        let process_refs: Vec<&_> = processes.iter().collect();
-                        println!("{}", TableFormatter::format_process_list(&process_refs));
-                    }
-                }
-                Err(e) => {
-                    print_error(&format!("Error getting daemon status: {}", e));
-                }
+async fn handle_info(local: bool) -> Result<()> {
+    let cli_version = env!("CARGO_PKG_VERSION");
+    let endpoint = rpm::ipc::endpoint()?;
+
+    print_header("RPM Info");
+    println!("{:<14} {}", "CLI version:".bright_white(), cli_version);
+
+    let client = rpm::ipc::IpcClient::new().await?;
+    match client.handshake().await {
+        Ok(daemon) => {
+            println!("{:<14} {}", "Daemon:".bright_white(), format!("{} (PID {})", daemon.version, daemon.pid).bright_green());
+            if let Some(started_at) = daemon.started_at {
+                println!("{:<14} {}", "Started:".bright_white(), format_timestamp(started_at, local));
+            }
+            if daemon.version != cli_version {
+                print_warning("CLI and daemon versions differ; restart the daemon after upgrading");
+            }
+            if daemon.endpoint != endpoint {
+                println!("{:<14} {}", "Daemon IPC:".bright_white(), daemon.endpoint);
             }
         }
-        Err(_) => {
-            print_error("Daemon is not running");
-            println!();
-            print_info("Start the daemon with: rpm daemon");
+        Err(e) => {
+            println!("{:<14} {}", "Daemon:".bright_white(), "not running".bright_red());
+            tracing::debug!("Handshake failed: {}", e);
         }
     }
+
+    println!("{:<14} {}", "IPC:".bright_white(), endpoint);
+    println!("{:<14} {}", "PID file:".bright_white(), rpm::config::get_daemon_pid_path()?.display());
+    println!("{:<14} {}", "Config dir:".bright_white(), rpm::config::get_config_dir()?.display());
+    println!("{:<14} {}", "Data dir:".bright_white(), rpm::config::get_data_dir()?.display());
+    println!("{:<14} {}", "Logs dir:".bright_white(), rpm::config::get_logs_dir()?.display());
+    Ok(())
+}
+
+fn handle_man() -> Result<()> {
+    use clap::CommandFactory;
+
+    let man = clap_mangen::Man::new(Cli::command());
+    man.render(&mut std::io::stdout())?;
+    Ok(())
+}
+
+/// Silent on purpose: the exit code is the answer. 6 means the process exists
+/// but isn't running, 4 that there is no such process; only a daemon that
+/// can't be reached is reported, as for any other command.
+async fn handle_is_running(name: String) -> Result<()> {
+    let client = rpm::ipc::IpcClient::new().await?;
+    match client.get_process_info(&name).await {
+        Ok(info) if info.status == rpm::process::ProcessStatus::Running => Ok(()),
+        Ok(_) => process::exit(rpm::error::NOT_RUNNING_EXIT_CODE),
+        Err(e @ RpmError::ProcessNotFound(_)) => process::exit(e.exit_code()),
+        Err(e) => Err(e),
+    }
+}
+
+async fn handle_status(fail_on: rpm::process::FailOn) -> Result<()> {
+    let client = rpm::ipc::IpcClient::new().await?;
+    let processes = client.list_summaries().await?;
+
+    print_header("RPM Daemon Status");
+    match client.handshake().await {
+        Ok(daemon) => print_success(&format!("Daemon is running (PID {})", daemon.pid)),
+        Err(_) => print_success("Daemon is running"),
+    }
+    
+    let running = processes.iter().filter(|p| p.status == rpm::process::ProcessStatus::Running).count();
+    let stopped = processes.iter().filter(|p| p.status == rpm::process::ProcessStatus::Stopped).count();
+    let errored = processes.iter().filter(|p| p.status == rpm::process::ProcessStatus::Errored).count();
+    let crash_looped = processes.iter().filter(|p| p.status == rpm::process::ProcessStatus::CrashLooped).count();
+    
+    println!();
+    println!("{:<20} {}", "Total processes:".bright_white(), processes.len().to_string().bright_yellow());
+    println!("{:<20} {}", "Running:".bright_white(), running.to_string().bright_green());
+    println!("{:<20} {}", "Stopped:".bright_white(), stopped.to_string().bright_red());
+    println!("{:<20} {}", "Errored:".bright_white(), errored.to_string().bright_red());
+    println!("{:<20} {}", "Crash-looped:".bright_white(), crash_looped.to_string().bright_magenta());
+    
+    if !processes.is_empty() {
+        println!();
+        let process_refs: Vec<&_> = processes.iter().collect();
+        println!("{}", TableFormatter::format_process_list(&process_refs, false, None));
+    }
+
+    let unhealthy: Vec<String> = processes
+        .iter()
+        .filter(|p| fail_on.fails(&p.status))
+        .map(|p| format!("{} ({})", p.name, p.status))
+        .collect();
+    if !unhealthy.is_empty() {
+        return Err(RpmError::Process(format!("Unhealthy processes: {}", unhealthy.join(", "))));
+    }
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,108 @@
+use crate::process::{ProcessInfo, ProcessManager, ProcessStatus};
+use crate::{Result, RpmError};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+
+/// Parses `metrics_bind` and `metrics_port` into the address to listen on.
+/// Binding anywhere but loopback is allowed, with a warning, since nothing
+/// guards the endpoint: anyone who can reach it can read process names and
+/// usage.
+pub fn bind_address(bind: &str, port: u16) -> Result<SocketAddr> {
+    let ip: IpAddr = bind.parse().map_err(|_| {
+        RpmError::Config(format!("Invalid metrics_bind '{}': expected an IP address such as 127.0.0.1", bind))
+    })?;
+    if !ip.is_loopback() {
+        tracing::warn!(
+            "Metrics are served on non-loopback address {} without authentication; restrict access to it with a firewall",
+            ip
+        );
+    }
+    Ok(SocketAddr::new(ip, port))
+}
+
+/// Serves `GET /metrics` in the Prometheus text format until the daemon
+/// exits. Control IPC is unaffected and stays local.
+pub async fn serve(address: SocketAddr, manager: Arc<Mutex<ProcessManager>>) -> Result<()> {
+    let listener = TcpListener::bind(address)
+        .await
+        .map_err(|e| RpmError::Daemon(format!("Failed to bind metrics server to {}: {}", address, e)))?;
+    tracing::info!("Serving metrics on http://{}/metrics", address);
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let manager = manager.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, manager).await {
+                tracing::debug!("Metrics connection failed: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(stream: TcpStream, manager: Arc<Mutex<ProcessManager>>) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    // Drain the headers; nothing in them matters here.
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => {
+            let processes = manager.lock().await.list_processes().await;
+            let body = render(&processes);
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        }
+        _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+    };
+
+    let mut stream = reader.into_inner();
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// One gauge or counter per process for status, usage and restart counts.
+/// Process names are limited to `[A-Za-z0-9._-]`, so labels need no escaping.
+fn render(processes: &[ProcessInfo]) -> String {
+    type Sample = fn(&ProcessInfo) -> f64;
+    let metrics: [(&str, &str, &str, Sample); 6] = [
+        ("rpm_process_up", "gauge", "Whether the process is running (1) or not (0).", |p| {
+            if p.status == ProcessStatus::Running { 1.0 } else { 0.0 }
+        }),
+        ("rpm_process_cpu_percent", "gauge", "CPU usage in percent of one core.", |p| p.cpu_usage),
+        ("rpm_process_memory_bytes", "gauge", "Resident memory in bytes.", |p| p.memory_usage as f64),
+        ("rpm_process_uptime_seconds", "gauge", "Seconds since the process started, 0 when not running.", |p| {
+            p.uptime.unwrap_or(0) as f64
+        }),
+        ("rpm_process_restarts_total", "counter", "Restarts other than crash restarts.", |p| f64::from(p.restarts)),
+        ("rpm_process_crashes_total", "counter", "Crash restarts.", |p| f64::from(p.crashes)),
+    ];
+
+    let mut output = String::new();
+    for (name, kind, help, sample) in metrics {
+        output.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n", name, help, name, kind));
+        for process in processes {
+            output.push_str(&format!(
+                "{}{{name=\"{}\",id=\"{}\"}} {}\n",
+                name,
+                process.name,
+                process.id,
+                sample(process)
+            ));
+        }
+    }
+    output
+}
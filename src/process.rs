@@ -1,15 +1,26 @@
-use crate::{cli::ProcessConfig, Result, RpmError};
+use crate::{cli::ProcessConfig, logs, watch::FileWatcher, Result, RpmError};
 use chrono::{DateTime, Utc};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::process::Stdio;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
 use tokio::process::Command as TokioCommand;
+use tokio::sync::{broadcast, mpsc};
 use tokio::time::{Duration, Instant};
-use uuid::Uuid;
+
+/// Most log lines the daemon returns for one request; larger requests are cut
+/// down to the newest this many.
+pub const MAX_LOG_LINES: usize = 10_000;
+
+/// How long a restart waits for the old run to release `ready_port`.
+pub const PORT_FREE_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessInfo {
-    pub id: String,
+    /// Numeric id, usable in place of the name. See `ProcessManager::next_free_id`.
+    pub id: u32,
     pub name: String,
     pub command: String,
     pub status: ProcessStatus,
@@ -17,16 +28,91 @@ pub struct ProcessInfo {
     pub cpu_usage: f64,
     pub memory_usage: u64,
     pub started_at: DateTime<Utc>,
+    /// Restarts by hand, for memory limits or file changes. Crash restarts
+    /// are counted in `crashes` instead.
     pub restarts: u32,
+    /// Times the process exited on its own and was restarted for it.
+    #[serde(default)]
+    pub crashes: u32,
+    /// What triggered the most recent restart, if the process has restarted.
+    #[serde(default)]
+    pub last_restart_reason: Option<RestartReason>,
+    /// When the most recent restart happened, automatic or manual.
+    #[serde(default)]
+    pub last_restart_at: Option<DateTime<Utc>>,
+    /// Set when there is no way to sample CPU and memory here (e.g. no
+    /// `/proc`); the usage fields are then meaningless rather than zero.
+    #[serde(default)]
+    pub stats_unavailable: bool,
+    /// Thread count of the main process, where the platform reports it.
+    #[serde(default)]
+    pub threads: Option<u32>,
+    /// Resources over their warning threshold as of the last check.
+    #[serde(default)]
+    pub warnings: Vec<Resource>,
+    /// Bytes per second the process read from storage between the last two
+    /// samples, from `/proc/<pid>/io`. Linux only.
+    #[serde(default)]
+    pub disk_read: Option<u64>,
+    /// Bytes per second written to storage, like `disk_read`.
+    #[serde(default)]
+    pub disk_write: Option<u64>,
+    /// Program and arguments the process was last spawned with, as resolved
+    /// by `resolve_argv`.
+    #[serde(default)]
+    pub exec: Option<Vec<String>>,
+    /// Why the last start failed, cleared by the next successful start.
+    #[serde(default)]
+    pub start_error: Option<String>,
+    /// The environment the process was last spawned with, values of
+    /// secret-looking variables masked. Only filled in for a single process,
+    /// never in lists.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_env: Option<BTreeMap<String, String>>,
+    /// Set by `rpm pause`: the daemon neither restarts nor memory-guards
+    /// the process until `rpm resume`.
+    #[serde(default)]
+    pub monitoring_paused: bool,
+    /// Seconds the process has been running, measured by the daemon's clock
+    /// when the info is served. `None` when the process is not running.
+    #[serde(default)]
+    pub uptime: Option<u64>,
+    #[serde(default)]
+    pub out_log_path: Option<String>,
+    /// `None` when stderr is merged into the stdout log.
+    #[serde(default)]
+    pub err_log_path: Option<String>,
     pub config: ProcessConfig,
 }
 
+/// The part of `ProcessInfo` the process table shows, so `rpm list` and
+/// `rpm monitor` don't have the daemon serialize every definition and env.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessSummary {
+    pub id: u32,
+    pub name: String,
+    pub status: ProcessStatus,
+    pub cpu_usage: f64,
+    pub memory_usage: u64,
+    pub restarts: u32,
+    pub uptime: Option<u64>,
+    pub stats_unavailable: bool,
+    pub warnings: Vec<Resource>,
+    pub last_restart_at: Option<DateTime<Utc>>,
+    /// The definition's `comment`, for `rpm list --show-desc`.
+    pub comment: Option<String>,
+    pub monitoring_paused: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ProcessStatus {
     Running,
     Stopped,
     Errored,
     Restarting,
+    /// Crashed too often within the restart window and is no longer being
+    /// restarted automatically.
+    CrashLooped,
 }
 
 impl std::fmt::Display for ProcessStatus {
@@ -36,6 +122,152 @@ impl std::fmt::Display for ProcessStatus {
             ProcessStatus::Stopped => write!(f, "stopped"),
             ProcessStatus::Errored => write!(f, "errored"),
             ProcessStatus::Restarting => write!(f, "restarting"),
+            ProcessStatus::CrashLooped => write!(f, "crash-looped"),
+        }
+    }
+}
+
+/// Which process states make `rpm status` exit with an error.
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum FailOn {
+    /// Errored or crash-looped processes.
+    #[default]
+    Errored,
+    /// Any process that isn't running, including stopped ones.
+    AnyNotRunning,
+}
+
+impl FailOn {
+    pub fn fails(self, status: &ProcessStatus) -> bool {
+        match self {
+            FailOn::Errored => matches!(status, ProcessStatus::Errored | ProcessStatus::CrashLooped),
+            FailOn::AnyNotRunning => *status != ProcessStatus::Running,
+        }
+    }
+}
+
+/// Why a process was restarted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum RestartReason {
+    /// `rpm restart` or `rpm reload`.
+    Manual,
+    /// Autorestart after the process exited on its own.
+    Crash { exit_code: Option<i32> },
+    /// The polled memory usage went over `max_memory`.
+    MemoryLimit,
+    /// A watched file changed.
+    FileChange,
+}
+
+/// What `ProcessManager::start_process` started, so callers can refer to
+/// the process right away.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartResult {
+    /// The process name.
+    pub id: String,
+    pub numeric_id: u32,
+    /// `None` if the platform didn't report one.
+    pub pid: Option<u32>,
+}
+
+/// Everything known about a process when it exits on its own, so a
+/// subscriber can act on it without looking the process up again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashEvent {
+    pub at: DateTime<Utc>,
+    pub id: u32,
+    pub name: String,
+    pub command: String,
+    pub cwd: Option<String>,
+    /// Names of the environment variables set for the process; values are
+    /// left out since they often hold secrets.
+    pub env_keys: Vec<String>,
+    /// `None` if the process was killed by a signal.
+    pub exit_code: Option<i32>,
+    pub uptime_secs: u64,
+    /// Restarts before this crash, not counting crash restarts.
+    pub restarts: u32,
+    /// Crash restarts before this crash.
+    #[serde(default)]
+    pub crashes: u32,
+    /// Whether the crash tripped crash-loop detection, so RPM won't restart it.
+    pub crash_looped: bool,
+    /// The process's `--meta` annotations, e.g. the version that crashed.
+    #[serde(default)]
+    pub metadata: std::collections::BTreeMap<String, String>,
+}
+
+impl CrashEvent {
+    fn new(process: &ManagedProcess) -> Self {
+        let info = &process.info;
+        let env = info.config.resolved_env().unwrap_or_else(|_| info.config.env.clone());
+        let at = Utc::now();
+        CrashEvent {
+            at,
+            id: info.id,
+            name: info.name.clone(),
+            command: info.command.clone(),
+            cwd: info.config.cwd.clone(),
+            env_keys: env.into_iter().map(|(key, _)| key).collect(),
+            exit_code: process.last_exit_code,
+            uptime_secs: at.signed_duration_since(info.started_at).num_seconds().max(0) as u64,
+            restarts: info.restarts,
+            crashes: info.crashes,
+            crash_looped: info.status == ProcessStatus::CrashLooped,
+            metadata: info.config.metadata.clone(),
+        }
+    }
+}
+
+/// A resource with a soft warning threshold.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Resource {
+    Memory,
+    Cpu,
+}
+
+impl std::fmt::Display for Resource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Resource::Memory => write!(f, "memory"),
+            Resource::Cpu => write!(f, "cpu"),
+        }
+    }
+}
+
+/// Sent when a process's usage goes over its `warn_memory` or `warn_cpu`
+/// threshold, and again when it drops back under.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdEvent {
+    pub at: DateTime<Utc>,
+    pub id: u32,
+    pub name: String,
+    pub resource: Resource,
+    /// MB for memory, percent of one core for CPU, like the threshold.
+    pub usage: f64,
+    pub threshold: f64,
+    /// `false` when usage dropped back under the threshold.
+    pub exceeded: bool,
+}
+
+/// Something that happened to a managed process, from
+/// `ProcessManager::subscribe_events`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+pub enum ProcessEvent {
+    Crash(CrashEvent),
+    Threshold(ThresholdEvent),
+}
+
+impl std::fmt::Display for RestartReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RestartReason::Manual => write!(f, "manual"),
+            RestartReason::Crash { exit_code: Some(code) } => write!(f, "crash (exit code {})", code),
+            RestartReason::Crash { exit_code: None } => write!(f, "crash (killed by signal)"),
+            RestartReason::MemoryLimit => write!(f, "memory limit"),
+            RestartReason::FileChange => write!(f, "file change"),
         }
     }
 }
@@ -44,14 +276,55 @@ pub struct ManagedProcess {
     pub info: ProcessInfo,
     pub child: Option<tokio::process::Child>,
     pub last_restart: Option<Instant>,
-    pub log_buffer: Vec<String>,
+    pub log_buffer: logs::LogBuffer,
+    pub log_channel: logs::LogChannel,
+    pub watcher: Option<FileWatcher>,
+    /// Set when the child exits without being stopped through RPM, which is
+    /// the only case autorestart applies to.
+    pub exited_unexpectedly: bool,
+    /// The environment of the current or last run as it was spawned: the
+    /// daemon's inherited or forwarded variables with the process's env and
+    /// profile applied. Served masked by `get_process_info`, as is `config.env`.
+    pub start_env: Option<BTreeMap<String, String>>,
+    /// Cleared by `rpm pause` so `monitor_processes` and watch restarts
+    /// leave the process alone. Not saved: a daemon restart resumes it.
+    pub monitored: bool,
+    /// Exit code of the last run, `None` if it was killed by a signal or
+    /// hasn't exited.
+    pub last_exit_code: Option<i32>,
+    /// When recent crash restarts happened, oldest first, for crash-loop
+    /// detection. Cleared by a manual restart.
+    pub crash_restarts: VecDeque<Instant>,
+    /// Until when health checks are skipped after the last start.
+    pub warmup_until: Option<Instant>,
+    /// When a restart that is waiting out its delay is due to start the
+    /// process. Cleared by any start, and by a stop, which cancels it.
+    next_start_at: Option<Instant>,
+    /// When CPU and memory were last sampled, to space samples out by
+    /// `sample_interval`.
+    last_sampled: Option<Instant>,
+    /// CPU time (clock ticks) and when it was read, for computing CPU usage
+    /// from `/proc` as the difference between two samples.
+    #[cfg(unix)]
+    cpu_sample: Option<(u64, Instant)>,
+    /// Cumulative bytes read and written and when they were read, for
+    /// computing disk I/O rates the same way.
+    #[cfg(target_os = "linux")]
+    io_sample: Option<(u64, u64, Instant)>,
+    /// Start time of the spawned process in clock ticks after boot, to tell
+    /// it apart from an unrelated process that later reuses its pid.
+    #[cfg(target_os = "linux")]
+    start_ticks: Option<u64>,
+    /// The cgroup enforcing this run's limits, when cgroups are in use.
+    #[cfg(target_os = "linux")]
+    pub cgroup: Option<crate::cgroup::Cgroup>,
 }
 
 impl ManagedProcess {
-    pub fn new(config: ProcessConfig) -> Self {
-        let id = Uuid::new_v4().to_string();
+    pub fn new(id: u32, config: ProcessConfig) -> Self {
+        let (out_log_path, err_log_path) = log_paths(&config);
         let info = ProcessInfo {
-            id: id.clone(),
+            id,
             name: config.name.clone(),
             command: config.command.clone(),
             status: ProcessStatus::Stopped,
@@ -60,6 +333,21 @@ impl ManagedProcess {
             memory_usage: 0,
             started_at: Utc::now(),
             restarts: 0,
+            crashes: 0,
+            last_restart_reason: None,
+            last_restart_at: None,
+            stats_unavailable: false,
+            threads: None,
+            warnings: Vec::new(),
+            disk_read: None,
+            disk_write: None,
+            exec: None,
+            start_error: None,
+            start_env: None,
+            monitoring_paused: false,
+            uptime: None,
+            out_log_path,
+            err_log_path,
             config,
         };
 
@@ -67,81 +355,208 @@ impl ManagedProcess {
             info,
             child: None,
             last_restart: None,
-            log_buffer: Vec::new(),
+            log_buffer: logs::new_buffer(),
+            log_channel: logs::new_channel(),
+            watcher: None,
+            exited_unexpectedly: false,
+            start_env: None,
+            monitored: true,
+            last_exit_code: None,
+            crash_restarts: VecDeque::new(),
+            warmup_until: None,
+            next_start_at: None,
+            last_sampled: None,
+            #[cfg(unix)]
+            cpu_sample: None,
+            #[cfg(target_os = "linux")]
+            io_sample: None,
+            #[cfg(target_os = "linux")]
+            start_ticks: None,
+            #[cfg(target_os = "linux")]
+            cgroup: None,
         }
     }
 
+    /// Replaces the process definition. Takes effect on the next start.
+    pub fn apply_config(&mut self, config: ProcessConfig) {
+        let (out_log_path, err_log_path) = log_paths(&config);
+        self.info.command = config.command.clone();
+        self.info.out_log_path = out_log_path;
+        self.info.err_log_path = err_log_path;
+        self.info.config = config;
+    }
+
+    /// Keeps output in memory and on `log_channel` only, writing no log
+    /// files. Lasts until the next `apply_config`.
+    pub fn disable_log_files(&mut self) {
+        self.info.out_log_path = None;
+        self.info.err_log_path = None;
+    }
+
     pub async fn start(&mut self) -> Result<()> {
+        self.next_start_at = None;
         if self.info.status == ProcessStatus::Running {
             return Ok(());
         }
 
-        #[cfg(unix)]
-        let mut cmd = {
-            let mut cmd = TokioCommand::new("sh");
-            cmd.arg("-c").arg(&self.info.command);
-            cmd
+        if let Some(cwd) = &self.info.config.cwd {
+            if !std::path::Path::new(cwd).is_dir() {
+                let message = format!("working directory no longer exists: {}", cwd);
+                return Err(self.fail_start(RpmError::Process(message)));
+            }
+        }
+
+        let env = match self.info.config.resolved_env() {
+            Ok(env) => env,
+            Err(e) => return Err(self.fail_start(e)),
         };
 
-        #[cfg(windows)]
-        let mut cmd = {
-            let parts: Vec<&str> = self.info.command.split_whitespace().collect();
-            if parts.is_empty() {
-                return Err(RpmError::Process("Empty command".to_string()));
-            }
-            
-            // Try to find the executable in PATH if it's not already a full path
-            let executable = if std::path::Path::new(parts[0]).exists() {
-                parts[0].to_string()
-            } else {
-                match find_executable_in_path(parts[0]) {
-                    Some(path) => {
-                        tracing::info!("Found executable '{}' at path: {}", parts[0], path);
-                        path
-                    }
-                    None => {
-                        tracing::warn!("Could not find executable '{}' in PATH", parts[0]);
-                        parts[0].to_string()
-                    }
-                }
-            };
-            
-            let mut cmd = TokioCommand::new(executable);
-            if parts.len() > 1 {
-                cmd.args(&parts[1..]);
-            }
-            cmd
+        let argv = match resolve_argv(&self.info.config, &env) {
+            Ok(argv) => argv,
+            Err(e) => return Err(self.fail_start(e)),
         };
+        let mut cmd = TokioCommand::new(&argv[0]);
+        cmd.args(&argv[1..]);
+        self.info.exec = Some(argv);
 
         if let Some(cwd) = &self.info.config.cwd {
             cmd.current_dir(cwd);
         }
 
-        for (key, value) in &self.info.config.env {
-            cmd.env(key, value);
+        // Everything the child ends up with, inherited variables included.
+        let mut start_env = BTreeMap::new();
+        let lossy = |key: &std::ffi::OsStr, value: &std::ffi::OsStr| {
+            (key.to_string_lossy().into_owned(), value.to_string_lossy().into_owned())
+        };
+        let config = &self.info.config;
+        if !config.passthrough_env.is_empty() || !config.env_prefix.is_empty() {
+            cmd.env_clear();
+            for key in &config.passthrough_env {
+                if let Some(value) = std::env::var_os(key) {
+                    start_env.insert(key.clone(), value.to_string_lossy().into_owned());
+                    cmd.env(key, value);
+                }
+            }
+            for (key, value) in std::env::vars_os() {
+                let matches = key
+                    .to_str()
+                    .is_some_and(|key| config.env_prefix.iter().any(|prefix| key.starts_with(prefix.as_str())));
+                if matches {
+                    let (name, text) = lossy(&key, &value);
+                    start_env.insert(name, text);
+                    cmd.env(key, value);
+                }
+            }
+        } else {
+            start_env.extend(std::env::vars_os().map(|(key, value)| lossy(&key, &value)));
+        }
+        for (key, value) in env {
+            cmd.env(&key, &value);
+            start_env.insert(key, value);
+        }
+
+        let merged_reader = if self.info.config.merge_logs {
+            let (reader, writer) = std::io::pipe()?;
+            cmd.stdout(writer.try_clone()?).stderr(writer);
+            Some(reader)
+        } else {
+            cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+            None
+        };
+        cmd.stdin(if self.info.config.stdin { Stdio::piped() } else { Stdio::null() });
+
+        #[cfg(target_os = "linux")]
+        let cgroup = crate::cgroup::Cgroup::create(&self.info.config);
+        #[cfg(target_os = "linux")]
+        if let Some(cgroup) = &cgroup {
+            cgroup.prepare(&mut cmd);
         }
 
-        cmd.stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .stdin(Stdio::null());
+        let spawned = cmd.spawn();
+        // Release our copies of the pipe write ends so the readers see EOF when the child exits.
+        drop(cmd);
+
+        match spawned {
+            Ok(mut child) => {
+                let rate_limit = self.info.config.log_rate_limit;
+                let out_path = self.info.out_log_path.as_ref().map(std::path::PathBuf::from);
+                let err_path = self.info.err_log_path.as_ref().map(std::path::PathBuf::from);
+                if let Some(reader) = merged_reader {
+                    logs::spawn_pipe_reader(reader, &self.info.name, out_path, self.log_buffer.clone(), self.log_channel.clone(), rate_limit);
+                } else {
+                    if let Some(stdout) = child.stdout.take() {
+                        logs::spawn_reader(stdout, &self.info.name, logs::LogStream::Stdout, out_path, self.log_buffer.clone(), self.log_channel.clone(), rate_limit);
+                    }
+                    if let Some(stderr) = child.stderr.take() {
+                        logs::spawn_reader(stderr, &self.info.name, logs::LogStream::Stderr, err_path, self.log_buffer.clone(), self.log_channel.clone(), rate_limit);
+                    }
+                }
 
-        match cmd.spawn() {
-            Ok(child) => {
                 self.info.pid = child.id();
                 self.info.status = ProcessStatus::Running;
+                self.last_sampled = None;
+                #[cfg(unix)]
+                {
+                    self.cpu_sample = None;
+                }
+                #[cfg(target_os = "linux")]
+                {
+                    self.io_sample = None;
+                    self.start_ticks = self.info.pid.and_then(get_start_ticks_linux);
+                }
+                self.exited_unexpectedly = false;
+                self.info.start_error = None;
+                self.start_env = Some(start_env);
                 self.info.started_at = Utc::now();
+                self.warmup_until = Some(Instant::now() + Duration::from_secs(self.info.config.warmup));
                 self.child = Some(child);
+                #[cfg(target_os = "linux")]
+                {
+                    self.cgroup = cgroup;
+                }
                 tracing::info!("Started process '{}' with PID {:?}", self.info.name, self.info.pid);
                 Ok(())
             }
             Err(e) => {
+                #[cfg(target_os = "linux")]
+                if let Some(cgroup) = cgroup {
+                    cgroup.remove();
+                }
                 self.info.status = ProcessStatus::Errored;
+                self.info.start_error = Some(e.to_string());
                 Err(RpmError::Process(format!("Failed to start process '{}': {}", self.info.name, e)))
             }
         }
     }
 
+    /// Marks the process errored for a start that failed before spawning,
+    /// keeping `error` as the reason `rpm show` gives. Retrying can't help
+    /// until the directory, program or definition is fixed, so autorestart
+    /// doesn't try again every few seconds.
+    fn fail_start(&mut self, error: RpmError) -> RpmError {
+        self.info.status = ProcessStatus::Errored;
+        self.info.start_error = Some(error.detail());
+        self.exited_unexpectedly = false;
+        error
+    }
+
+    /// Stops the process, allowing it the configured `kill_timeout` to exit.
     pub async fn stop(&mut self) -> Result<()> {
+        self.stop_within(self.kill_timeout()).await
+    }
+
+    fn kill_timeout(&self) -> Duration {
+        Duration::from_secs(self.info.config.kill_timeout)
+    }
+
+    /// Asks the process to exit and force-kills it if it is still running
+    /// after `grace`.
+    pub async fn stop_within(&mut self, grace: Duration) -> Result<()> {
+        if self.next_start_at.take().is_some() {
+            // Cancels the restart, crash restart included.
+            self.info.status = ProcessStatus::Stopped;
+            self.exited_unexpectedly = false;
+        }
         if let Some(mut child) = self.child.take() {
             #[cfg(unix)]
             {
@@ -150,6 +565,15 @@ impl ManagedProcess {
                         libc::kill(pid as i32, libc::SIGTERM);
                     }
                 }
+
+                if tokio::time::timeout(grace, child.wait()).await.is_err() {
+                    tracing::warn!(
+                        "Process '{}' did not exit within {}s, killing it",
+                        self.info.name,
+                        grace.as_secs()
+                    );
+                    let _ = child.kill().await;
+                }
             }
 
             #[cfg(windows)]
@@ -160,6 +584,7 @@ impl ManagedProcess {
             }
 
             let _ = child.wait().await;
+            self.release_cgroup();
             self.info.status = ProcessStatus::Stopped;
             self.info.pid = None;
             tracing::info!("Stopped process '{}'", self.info.name);
@@ -167,15 +592,125 @@ impl ManagedProcess {
         Ok(())
     }
 
-    pub async fn restart(&mut self) -> Result<()> {
-        self.stop().await?;
-        tokio::time::sleep(Duration::from_millis(500)).await;
-        self.info.restarts += 1;
+    /// Kills the process and everything it spawned right away, skipping the
+    /// SIGTERM and `kill_timeout` wait of `stop`.
+    pub async fn force_kill(&mut self) -> Result<()> {
+        if self.next_start_at.take().is_some() {
+            // Cancels the restart, crash restart included.
+            self.info.status = ProcessStatus::Stopped;
+            self.exited_unexpectedly = false;
+        }
+        if let Some(mut child) = self.child.take() {
+            if let Some(pid) = child.id() {
+                self.kill_tree(pid);
+            }
+            let _ = child.kill().await;
+            let _ = child.wait().await;
+            self.release_cgroup();
+            self.info.status = ProcessStatus::Stopped;
+            self.info.pid = None;
+            tracing::info!("Force-killed process '{}'", self.info.name);
+        }
+        Ok(())
+    }
+
+    /// Sends SIGKILL to the process's descendants, through its cgroup when it
+    /// has one.
+    #[cfg(unix)]
+    fn kill_tree(&self, pid: u32) {
+        #[cfg(target_os = "linux")]
+        {
+            if let Some(cgroup) = &self.cgroup {
+                match cgroup.kill() {
+                    Ok(()) => return,
+                    Err(e) => tracing::debug!("Failed to kill cgroup of '{}': {}", self.info.name, e),
+                }
+            }
+            for descendant in descendants_linux(pid) {
+                unsafe {
+                    libc::kill(descendant as i32, libc::SIGKILL);
+                }
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = pid;
+    }
+
+    #[cfg(windows)]
+    fn kill_tree(&self, pid: u32) {
+        let status = std::process::Command::new("taskkill")
+            .args(["/F", "/T", "/PID", &pid.to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        if let Err(e) = status {
+            tracing::debug!("Failed to run taskkill for '{}': {}", self.info.name, e);
+        }
+    }
+
+    /// Writes `text` and a trailing newline to the child's stdin pipe.
+    pub async fn send_input(&mut self, text: &str) -> Result<()> {
+        let child = self.child.as_mut().ok_or_else(|| {
+            RpmError::Process(format!("Process '{}' is not running", self.info.name))
+        })?;
+        let stdin = child.stdin.as_mut().ok_or_else(|| {
+            RpmError::Process(format!(
+                "Process '{}' was not started with stdin enabled",
+                self.info.name
+            ))
+        })?;
+
+        stdin.write_all(text.as_bytes()).await?;
+        stdin.write_all(b"\n").await?;
+        stdin.flush().await?;
+        Ok(())
+    }
+
+    /// How long to wait between stopping and starting on restart: the
+    /// `restart_delay`, doubled per consecutive crash restart with `backoff`
+    /// and stretched by a random share of up to `restart_jitter` of it so
+    /// processes that crash together don't all come back together, at most
+    /// `MAX_BACKOFF_MS` in all.
+    fn restart_backoff(&self, reason: &RestartReason) -> Duration {
+        let mut delay = self.info.config.restart_delay();
+        if self.info.config.backoff && matches!(reason, RestartReason::Crash { .. }) {
+            let doublings = self.crash_restarts.len().min(16) as u32;
+            delay *= 2u32.pow(doublings);
+        }
+        if let Some(jitter) = self.info.config.restart_jitter.filter(|jitter| *jitter > 0.0) {
+            delay = delay.mul_f64(1.0 + jitter.min(1.0) * random_fraction());
+        }
+        delay.min(Duration::from_millis(crate::cli::MAX_BACKOFF_MS))
+    }
+
+    /// Stops the process for a restart, giving the old run `grace` to exit,
+    /// and leaves it `Restarting`. Returns the delay after which it is due to
+    /// start again; `ProcessManager::restart_process` starts it then.
+    pub async fn restart_within(&mut self, grace: Duration, reason: RestartReason) -> Result<Duration> {
+        self.stop_within(grace).await?;
+        let delay = self.restart_backoff(&reason);
+        match reason {
+            RestartReason::Crash { .. } => {
+                self.info.crashes += 1;
+                self.crash_restarts.push_back(Instant::now());
+            }
+            RestartReason::Manual => {
+                self.info.restarts += 1;
+                self.crash_restarts.clear();
+            }
+            _ => self.info.restarts += 1,
+        }
+        self.info.last_restart_reason = Some(reason);
+        self.info.last_restart_at = Some(Utc::now());
         self.last_restart = Some(Instant::now());
-        self.start().await
+        self.info.status = ProcessStatus::Restarting;
+        self.next_start_at = Some(Instant::now() + delay);
+        Ok(delay)
     }
 
-    pub async fn check_status(&mut self) -> Result<()> {
+    /// Notices an exit and, if the last sample is at least `sample_interval`
+    /// old, samples CPU and memory usage. A new run is sampled right away.
+    pub async fn check_status(&mut self, sample_interval: Duration) -> Result<()> {
         if let Some(child) = &mut self.child {
             match child.try_wait() {
                 Ok(Some(status)) => {
@@ -186,44 +721,229 @@ impl ManagedProcess {
                     };
                     self.info.pid = None;
                     self.child = None;
+                    self.release_cgroup();
+                    self.exited_unexpectedly = true;
+                    self.last_exit_code = status.code();
                     tracing::info!("Process '{}' exited with status: {}", self.info.name, status);
+                    if let Some(min_uptime) = self.info.config.min_uptime {
+                        let uptime = Utc::now().signed_duration_since(self.info.started_at).num_seconds();
+                        if uptime >= min_uptime as i64 {
+                            // A stable run: earlier crashes no longer count.
+                            self.crash_restarts.clear();
+                        }
+                    }
                 }
                 Ok(None) => {
-                    self.update_resource_usage().await?;
+                    if self.last_sampled.is_none_or(|at| at.elapsed() >= sample_interval) {
+                        self.update_resource_usage().await?;
+                        self.last_sampled = Some(Instant::now());
+                    }
                 }
                 Err(e) => {
                     tracing::error!("Error checking process '{}': {}", self.info.name, e);
                     self.info.status = ProcessStatus::Errored;
                     self.child = None;
+                    self.exited_unexpectedly = true;
                 }
             }
         }
         Ok(())
     }
 
+    fn release_cgroup(&mut self) {
+        #[cfg(target_os = "linux")]
+        if let Some(cgroup) = self.cgroup.take() {
+            cgroup.remove();
+        }
+    }
+
+    /// Releases everything a stopped process leaves behind: its file watcher,
+    /// its cgroup and its log files, rotated segments included, so a process
+    /// recreated under the same name starts clean. Files chosen with
+    /// `--out-file`/`--err-file` belong to the user and are kept. RPM keeps no
+    /// per-process pid file, and on Windows the child handle is closed with
+    /// the process.
+    pub fn cleanup(&mut self) {
+        self.watcher = None;
+        self.release_cgroup();
+
+        let mut pool = logs::file_pool().lock().unwrap();
+        for stream in [logs::LogStream::Stdout, logs::LogStream::Stderr] {
+            let Ok(path) = logs::log_file_path(&self.info.name, stream) else {
+                continue;
+            };
+            if let Err(e) = pool.remove(&path) {
+                tracing::warn!("Failed to remove log file {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    /// Compares the last sampled usage with `warn_memory` and `warn_cpu`,
+    /// updating `info.warnings` and returning an event for each threshold
+    /// crossed in either direction since the last check.
+    fn check_thresholds(&mut self) -> Vec<ThresholdEvent> {
+        let running = self.info.status == ProcessStatus::Running && !self.info.stats_unavailable;
+        let config = &self.info.config;
+        let checks = [
+            (Resource::Memory, config.warn_memory.map(|mb| mb as f64), (self.info.memory_usage / 1024 / 1024) as f64),
+            (Resource::Cpu, config.warn_cpu.map(f64::from), self.info.cpu_usage),
+        ];
+
+        let mut events = Vec::new();
+        for (resource, threshold, usage) in checks {
+            let Some(threshold) = threshold else {
+                self.info.warnings.retain(|warned| *warned != resource);
+                continue;
+            };
+            let exceeded = running && usage > threshold;
+            if exceeded == self.info.warnings.contains(&resource) {
+                continue;
+            }
+            if exceeded {
+                self.info.warnings.push(resource);
+            } else {
+                self.info.warnings.retain(|warned| *warned != resource);
+            }
+            events.push(ThresholdEvent {
+                at: Utc::now(),
+                id: self.info.id,
+                name: self.info.name.clone(),
+                resource,
+                usage,
+                threshold,
+                exceeded,
+            });
+        }
+        events
+    }
+
+    /// Whether the process started less than `warmup` seconds ago.
+    pub fn warming_up(&self) -> bool {
+        self.warmup_until.is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Whether the kernel enforces this process's limits, making polled
+    /// enforcement unnecessary.
+    pub fn limits_enforced(&self) -> bool {
+        #[cfg(target_os = "linux")]
+        {
+            self.cgroup.is_some()
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            false
+        }
+    }
+
+    /// Samples CPU, memory and I/O for the current pid. On Linux the pid's
+    /// start time must still match the one recorded at spawn, so a stranger
+    /// that reused it is never sampled; on Windows the `Child` keeps a handle
+    /// open, which stops the pid from being reused while we hold it.
     async fn update_resource_usage(&mut self) -> Result<()> {
         if let Some(pid) = self.info.pid {
-            #[cfg(unix)]
-            {
-                if let Ok(usage) = get_process_usage_unix(pid) {
-                    self.info.cpu_usage = usage.0;
-                    self.info.memory_usage = usage.1;
-                }
+            self.info.stats_unavailable = !stats_supported();
+
+            #[cfg(target_os = "linux")]
+            if self.start_ticks.is_some() && get_start_ticks_linux(pid) != self.start_ticks {
+                tracing::warn!(
+                    "PID {} of process '{}' no longer belongs to it; skipping resource sampling",
+                    pid,
+                    self.info.name
+                );
+                return Ok(());
             }
 
+            #[cfg(unix)]
+            let usage = if proc_available() {
+                get_process_usage_unix(pid, &mut self.cpu_sample).ok()
+            } else {
+                sample_usage_fallback(pid)
+            };
             #[cfg(windows)]
+            let usage = sample_usage_fallback(pid);
+
+            if let Some((cpu_usage, memory_usage)) = usage {
+                self.info.cpu_usage = cpu_usage;
+                self.info.memory_usage = memory_usage;
+            }
+
+            #[cfg(target_os = "linux")]
             {
-                if let Ok(usage) = get_process_usage_windows(pid) {
-                    self.info.cpu_usage = usage.0;
-                    self.info.memory_usage = usage.1;
-                }
+                self.info.threads = get_thread_count_linux(pid);
+                let io = get_disk_io_linux(pid, &mut self.io_sample);
+                self.info.disk_read = io.map(|(read, _)| read);
+                self.info.disk_write = io.map(|(_, write)| write);
+            }
+
+            // The cgroup also counts children and page cache charged to the process.
+            #[cfg(target_os = "linux")]
+            if let Some(memory) = self.cgroup.as_ref().and_then(|cgroup| cgroup.memory_current()) {
+                self.info.memory_usage = memory;
             }
         }
         Ok(())
     }
 
+    /// Returns a copy of the process info with daemon-computed fields filled in.
+    pub fn snapshot(&self) -> ProcessInfo {
+        let mut info = self.info.clone();
+        info.uptime = self.uptime();
+        info.monitoring_paused = !self.monitored;
+        if info.status != ProcessStatus::Running {
+            info.threads = None;
+            info.disk_read = None;
+            info.disk_write = None;
+        }
+        info
+    }
+
+    /// Like `snapshot`, without cloning the definition.
+    pub fn summary(&self) -> ProcessSummary {
+        let info = &self.info;
+        ProcessSummary {
+            id: info.id,
+            name: info.name.clone(),
+            status: info.status.clone(),
+            cpu_usage: info.cpu_usage,
+            memory_usage: info.memory_usage,
+            restarts: info.restarts,
+            uptime: self.uptime(),
+            stats_unavailable: info.stats_unavailable,
+            warnings: info.warnings.clone(),
+            last_restart_at: info.last_restart_at,
+            comment: info.config.comment.clone(),
+            monitoring_paused: !self.monitored,
+        }
+    }
+
+    /// Seconds since the process started by the daemon's clock, `None` when
+    /// it isn't running.
+    fn uptime(&self) -> Option<u64> {
+        if self.info.status != ProcessStatus::Running {
+            return None;
+        }
+        Utc::now()
+            .signed_duration_since(self.info.started_at)
+            .to_std()
+            .ok()
+            .map(|uptime| uptime.as_secs())
+    }
+
+    /// Whether the process already used up its crash restarts for the
+    /// current window, i.e. it has crashed `max_restarts` times within
+    /// `window`. Timestamps older than the window are dropped.
+    pub fn crash_looping(&mut self, window: Duration, max_restarts: u32) -> bool {
+        if window.is_zero() || max_restarts == 0 {
+            return false;
+        }
+        while self.crash_restarts.front().is_some_and(|at| at.elapsed() > window) {
+            self.crash_restarts.pop_front();
+        }
+        self.crash_restarts.len() >= max_restarts as usize
+    }
+
     pub fn should_restart(&self) -> bool {
-        if !self.info.config.autorestart {
+        if !self.info.config.autorestart || !self.exited_unexpectedly {
             return false;
         }
 
@@ -241,32 +961,220 @@ impl ManagedProcess {
     }
 }
 
+fn log_paths(config: &ProcessConfig) -> (Option<String>, Option<String>) {
+    let log_path = |file: &Option<String>, stream| {
+        file.clone().or_else(|| {
+            logs::log_file_path(&config.name, stream)
+                .ok()
+                .map(|path| path.display().to_string())
+        })
+    };
+
+    let out_log_path = log_path(&config.out_file, logs::LogStream::Stdout);
+    let err_log_path = if config.merge_logs {
+        None
+    } else {
+        log_path(&config.err_file, logs::LogStream::Stderr)
+    };
+    (out_log_path, err_log_path)
+}
+
 pub struct ProcessManager {
-    processes: HashMap<String, ManagedProcess>,
+    /// In the order processes were added, which `save` and `resurrect` keep.
+    processes: IndexMap<String, ManagedProcess>,
     config: crate::config::Config,
+    /// Starts at `health_check_interval`; `rpm set-interval` changes it at runtime.
+    monitor_interval: Duration,
+    watch_tx: mpsc::UnboundedSender<String>,
+    watch_rx: Option<mpsc::UnboundedReceiver<String>>,
+    /// Names of restarted processes whose delay has passed, for
+    /// `spawn_supervisor` to start.
+    start_tx: mpsc::UnboundedSender<String>,
+    start_rx: Option<mpsc::UnboundedReceiver<String>>,
+    events: broadcast::Sender<ProcessEvent>,
 }
 
 impl ProcessManager {
+    /// Creates a manager using the config file, written with defaults if missing.
     pub async fn new() -> Result<Self> {
         let config = crate::config::Config::load().await?;
-        Ok(ProcessManager {
-            processes: HashMap::new(),
+        Ok(Self::with_config(config))
+    }
+
+    /// Creates a manager with `config` instead of the config file. Process
+    /// state is still saved to the data directory.
+    pub fn with_config(config: crate::config::Config) -> Self {
+        logs::configure_file_pool(&config);
+        #[cfg(target_os = "linux")]
+        crate::cgroup::configure(&config);
+        let (watch_tx, watch_rx) = mpsc::unbounded_channel();
+        let (start_tx, start_rx) = mpsc::unbounded_channel();
+        ProcessManager {
+            processes: IndexMap::new(),
+            monitor_interval: Duration::from_secs(config.health_check_interval.max(1)),
             config,
+            watch_tx,
+            watch_rx: Some(watch_rx),
+            start_tx,
+            start_rx: Some(start_rx),
+            events: broadcast::channel(64).0,
+        }
+    }
+
+    pub fn config(&self) -> &crate::config::Config {
+        &self.config
+    }
+
+    /// How long the daemon waits between `monitor_processes` runs.
+    pub fn monitor_interval(&self) -> Duration {
+        self.monitor_interval
+    }
+
+    /// Changes the monitor interval. Not saved to the config file.
+    pub fn set_monitor_interval(&mut self, secs: u64) -> Result<()> {
+        if secs == 0 {
+            return Err(RpmError::Config("Monitor interval must be at least 1 second".to_string()));
+        }
+        self.monitor_interval = Duration::from_secs(secs);
+        tracing::info!("Monitor interval set to {}s", secs);
+        Ok(())
+    }
+
+    /// Hands out the stream of process names whose watched files changed.
+    /// `spawn_supervisor` drains it and restarts each process as names arrive.
+    pub fn take_watch_events(&mut self) -> Option<mpsc::UnboundedReceiver<String>> {
+        self.watch_rx.take()
+    }
+
+    /// Receives a `ProcessEvent` each time a process exits on its own or
+    /// crosses a warning threshold.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<ProcessEvent> {
+        self.events.subscribe()
+    }
+
+    /// Starts the background work that keeps processes up: a task running
+    /// `monitor_processes` every `monitor_interval`, one restarting processes
+    /// whose watched files changed, and one starting restarted processes once
+    /// their delay has passed. Returns the monitor task; aborting it ends
+    /// supervision.
+    pub async fn spawn_supervisor(manager: &Arc<tokio::sync::Mutex<Self>>) -> tokio::task::JoinHandle<()> {
+        if let Some(mut due) = manager.lock().await.start_rx.take() {
+            let manager = manager.clone();
+            tokio::spawn(async move {
+                while let Some(name) = due.recv().await {
+                    manager.lock().await.start_scheduled(&name).await;
+                }
+            });
+        }
+
+        if let Some(mut watch_events) = manager.lock().await.take_watch_events() {
+            let manager = manager.clone();
+            tokio::spawn(async move {
+                while let Some(name) = watch_events.recv().await {
+                    let mut manager = manager.lock().await;
+                    if manager.processes.get(&name).is_some_and(|process| !process.monitored) {
+                        tracing::info!("Ignoring file changes for '{}': monitoring is paused", name);
+                        continue;
+                    }
+                    tracing::info!("Restarting process '{}' after file changes", name);
+                    if let Err(e) = manager.restart_process(&name, None, RestartReason::FileChange).await {
+                        tracing::error!("Failed to restart process '{}': {}", name, e);
+                    }
+                }
+            });
+        }
+
+        let manager = manager.clone();
+        tokio::spawn(async move {
+            // Re-read after each check so `set_monitor_interval` applies from the next one.
+            let mut period = manager.lock().await.monitor_interval();
+            loop {
+                tokio::time::sleep(period).await;
+                if let Ok(mut pm) = manager.try_lock() {
+                    if let Err(e) = pm.monitor_processes().await {
+                        tracing::error!("Error monitoring processes: {}", e);
+                    }
+                    period = pm.monitor_interval();
+                }
+            }
         })
     }
 
-    pub async fn start_process(&mut self, config: ProcessConfig) -> Result<String> {
-        let mut process = ManagedProcess::new(config);
+    fn attach_watcher(watch_tx: &mpsc::UnboundedSender<String>, process: &mut ManagedProcess) {
+        if process.info.config.watch.is_empty() || process.watcher.is_some() {
+            return;
+        }
+
+        match FileWatcher::new(&process.info.config, watch_tx.clone()) {
+            Ok(watcher) => process.watcher = Some(watcher),
+            Err(e) => tracing::error!("Failed to watch files for '{}': {}", process.info.name, e),
+        }
+    }
+
+    pub async fn start_process(&mut self, config: ProcessConfig) -> Result<StartResult> {
+        // Definitions from ecosystem files don't pass through `from_args`.
+        crate::cli::validate_name(&config.name)?;
+        if config.instances == 0 {
+            return Err(RpmError::Config(format!(
+                "Process '{}' needs at least 1 instance",
+                config.name
+            )));
+        }
+        if config.instances as usize > self.config.max_processes {
+            return Err(RpmError::Config(format!(
+                "Process '{}' asks for {} instances, more than max_processes ({})",
+                config.name, config.instances, self.config.max_processes
+            )));
+        }
+
+        // Starting over an existing name replaces that process but keeps its id.
+        let id = match self.processes.get(&config.name) {
+            Some(existing) => existing.info.id,
+            None => self.next_free_id(),
+        };
+        let mut process = ManagedProcess::new(id, config);
         process.start().await?;
-        let id = process.info.id.clone();
+        Self::attach_watcher(&self.watch_tx, &mut process);
+        let started = StartResult {
+            id: process.info.name.clone(),
+            numeric_id: id,
+            pid: process.info.pid,
+        };
         self.processes.insert(process.info.name.clone(), process);
         self.save_state().await?;
-        Ok(id)
+        Ok(started)
+    }
+
+    /// Ids are handed out lowest-free-first: a new process takes the smallest id
+    /// not held by another process, so the id of a deleted process is reused
+    /// by the next one started. Ids are saved with the process list and kept
+    /// across daemon restarts and resurrect.
+    fn next_free_id(&self) -> u32 {
+        let used: std::collections::HashSet<u32> =
+            self.processes.values().map(|process| process.info.id).collect();
+        (0..).find(|id| !used.contains(id)).unwrap_or_default()
+    }
+
+    /// Maps a numeric id to the name of the process holding it. Anything that
+    /// is already a process name, or isn't a known id, is returned unchanged.
+    pub fn resolve_target(&self, target: &str) -> String {
+        if self.processes.contains_key(target) {
+            return target.to_string();
+        }
+        target
+            .parse::<u32>()
+            .ok()
+            .and_then(|id| self.processes.values().find(|process| process.info.id == id))
+            .map(|process| process.info.name.clone())
+            .unwrap_or_else(|| target.to_string())
     }
 
-    pub async fn stop_process(&mut self, name: &str) -> Result<()> {
+    /// Stops a process. `grace` overrides its `kill_timeout` for this stop.
+    pub async fn stop_process(&mut self, name: &str, grace: Option<Duration>) -> Result<()> {
         if let Some(process) = self.processes.get_mut(name) {
-            process.stop().await?;
+            let grace = grace.unwrap_or_else(|| process.kill_timeout());
+            process.stop_within(grace).await?;
+            process.watcher = None;
             self.save_state().await?;
             Ok(())
         } else {
@@ -274,9 +1182,10 @@ impl ProcessManager {
         }
     }
 
-    pub async fn restart_process(&mut self, name: &str) -> Result<()> {
+    pub async fn force_kill_process(&mut self, name: &str) -> Result<()> {
         if let Some(process) = self.processes.get_mut(name) {
-            process.restart().await?;
+            process.force_kill().await?;
+            process.watcher = None;
             self.save_state().await?;
             Ok(())
         } else {
@@ -284,9 +1193,24 @@ impl ProcessManager {
         }
     }
 
-    pub async fn delete_process(&mut self, name: &str) -> Result<()> {
-        if let Some(mut process) = self.processes.remove(name) {
-            process.stop().await?;
+    /// Restarts a process. `grace` overrides its `kill_timeout` for this restart.
+    ///
+    /// Only the stop happens here. The start follows once the restart delay
+    /// has passed and the process's `ready_port` is free, from the
+    /// supervisor, so the manager isn't locked while waiting; callers that
+    /// need the outcome wait for it with `wait_for_restart`.
+    pub async fn restart_process(
+        &mut self,
+        name: &str,
+        grace: Option<Duration>,
+        reason: RestartReason,
+    ) -> Result<()> {
+        if let Some(process) = self.processes.get_mut(name) {
+            let grace = grace.unwrap_or_else(|| process.kill_timeout());
+            let delay = process.restart_within(grace, reason).await?;
+            Self::attach_watcher(&self.watch_tx, process);
+            let ready_port = process.info.config.ready_port;
+            self.schedule_start(name, delay, ready_port);
             self.save_state().await?;
             Ok(())
         } else {
@@ -294,50 +1218,393 @@ impl ProcessManager {
         }
     }
 
-    pub async fn list_processes(&self) -> Vec<&ProcessInfo> {
-        self.processes.values().map(|p| &p.info).collect()
+    /// Hands `name` to the supervisor after `delay` and, when it has a
+    /// `ready_port`, once nothing holds that port, waiting in a task of its own.
+    fn schedule_start(&self, name: &str, delay: Duration, ready_port: Option<u16>) {
+        let start_tx = self.start_tx.clone();
+        let name = name.to_string();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            if let Some(port) = ready_port {
+                if !wait_for_port_free(port, PORT_FREE_TIMEOUT).await {
+                    tracing::warn!(
+                        "Port {} still in use after {}s; starting '{}' anyway",
+                        port,
+                        PORT_FREE_TIMEOUT.as_secs(),
+                        name
+                    );
+                }
+            }
+            let _ = start_tx.send(name);
+        });
+    }
+
+    /// Starts a restarted process whose delay has passed, unless a stop or
+    /// another start got to it first or it was restarted again since.
+    async fn start_scheduled(&mut self, name: &str) {
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        if process.next_start_at.is_none_or(|at| at > Instant::now()) {
+            return;
+        }
+        match process.start().await {
+            Ok(()) => Self::attach_watcher(&self.watch_tx, process),
+            Err(e) => tracing::error!("Failed to restart process '{}': {}", name, e),
+        }
+    }
+
+    /// Waits until a restart of `name` has started the process or given up,
+    /// locking the manager only to look. Fails with the start error, if any.
+    pub async fn wait_for_restart(manager: &tokio::sync::Mutex<Self>, name: &str) -> Result<()> {
+        loop {
+            {
+                let pm = manager.lock().await;
+                let Some(process) = pm.processes.get(name) else {
+                    return Ok(());
+                };
+                if process.next_start_at.is_none() {
+                    return match (&process.info.status, &process.info.start_error) {
+                        (ProcessStatus::Errored, Some(error)) => Err(RpmError::Process(format!(
+                            "Failed to restart process '{}': {}",
+                            name, error
+                        ))),
+                        _ => Ok(()),
+                    };
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    /// Names of the processes in `group`, in the order they were added.
+    pub fn group_members(&self, group: &str) -> Vec<String> {
+        self.processes
+            .values()
+            .filter(|process| process.info.config.group.as_deref() == Some(group))
+            .map(|process| process.info.name.clone())
+            .collect()
+    }
+
+    /// Restarts the processes in `group` one at a time. After each restart it
+    /// waits `wait` and checks the process is still running before moving on,
+    /// stopping at the first one that isn't so the rest stay up. The manager
+    /// is only locked for each restart, not for the waits in between.
+    /// Returns how many processes were restarted.
+    pub async fn rolling_restart(
+        manager: &tokio::sync::Mutex<Self>,
+        group: &str,
+        update_env: bool,
+        grace: Option<Duration>,
+        wait: Duration,
+    ) -> Result<usize> {
+        let members = manager.lock().await.group_members(group);
+        if members.is_empty() {
+            return Err(RpmError::ProcessNotFound(format!("no processes in group '{}'", group)));
+        }
+
+        if update_env {
+            // Checked up front so the rollout doesn't stop halfway.
+            let pm = manager.lock().await;
+            for name in &members {
+                pm.definition_source(name)?;
+            }
+        }
+
+        for name in &members {
+            {
+                let mut pm = manager.lock().await;
+                if update_env {
+                    pm.refresh_env(name).await?;
+                }
+                pm.restart_process(name, grace, RestartReason::Manual).await?;
+            }
+            Self::wait_for_restart(manager, name).await?;
+            tracing::info!("Rolling restart of '{}': restarted '{}'", group, name);
+            tokio::time::sleep(wait).await;
+
+            let status = manager.lock().await.get_process_info(name).await?.status;
+            if status != ProcessStatus::Running {
+                return Err(RpmError::Process(format!(
+                    "'{}' is {} after restarting; stopped the rolling restart of '{}'",
+                    name, status, group
+                )));
+            }
+        }
+        Ok(members.len())
+    }
+
+    /// Re-reads the process definition from disk and restarts with it. The
+    /// definition comes from the ecosystem file the process was started from,
+    /// or from the saved process list otherwise.
+    pub async fn reload_process(&mut self, name: &str) -> Result<()> {
+        let config = self.load_definition(name).await?;
+
+        let process = self
+            .processes
+            .get_mut(name)
+            .ok_or_else(|| RpmError::ProcessNotFound(name.to_string()))?;
+        process.apply_config(config);
+        process.watcher = None;
+        self.restart_process(name, None, RestartReason::Manual).await
+    }
+
+    /// Replaces a process's definition with `config` and restarts it under
+    /// the new one, keeping its id and restart counts.
+    pub async fn update_process(&mut self, config: ProcessConfig) -> Result<()> {
+        crate::cli::validate_name(&config.name)?;
+        let process = self
+            .processes
+            .get_mut(&config.name)
+            .ok_or_else(|| RpmError::ProcessNotFound(config.name.clone()))?;
+
+        let name = config.name.clone();
+        process.apply_config(config);
+        process.watcher = None;
+        self.restart_process(&name, None, RestartReason::Manual).await
+    }
+
+    /// Sets `vars` in a process's env, dropping every other variable when
+    /// `replace` is set, and restarts it. Returns how many variables it has.
+    pub async fn update_env(&mut self, name: &str, vars: Vec<(String, String)>, replace: bool) -> Result<usize> {
+        let mut config = self
+            .processes
+            .get(name)
+            .ok_or_else(|| RpmError::ProcessNotFound(name.to_string()))?
+            .info
+            .config
+            .clone();
+        if replace {
+            config.env = vars;
+        } else {
+            config.merge_env(vars);
+        }
+        let count = config.env.len();
+        self.update_process(config).await?;
+        Ok(count)
+    }
+
+    /// Replaces only the env of a process with the one in its ecosystem file,
+    /// leaving the rest of its config as is. Takes effect on the next start;
+    /// the daemon's own environment is inherited at spawn as usual.
+    pub async fn refresh_env(&mut self, name: &str) -> Result<()> {
+        let source = self.definition_source(name)?;
+        let definition = crate::ecosystem::load_one(std::path::Path::new(&source), name)?;
+
+        if let Some(process) = self.processes.get_mut(name) {
+            process.info.config.env = definition.env;
+            process.info.config.env_profiles = definition.env_profiles;
+        }
+        Ok(())
+    }
+
+    async fn load_definition(&self, name: &str) -> Result<ProcessConfig> {
+        let source = self
+            .processes
+            .get(name)
+            .ok_or_else(|| RpmError::ProcessNotFound(name.to_string()))?
+            .info
+            .config
+            .source
+            .clone();
+
+        let config = match source {
+            Some(source) => crate::ecosystem::load_one(std::path::Path::new(&source), name)?,
+            None => self
+                .config
+                .load_process_configs()
+                .await?
+                .into_iter()
+                .find(|config| config.name == name)
+                .ok_or_else(|| {
+                    RpmError::Config(format!("No saved definition found for process '{}'", name))
+                })?,
+        };
+        Ok(config)
     }
 
-    pub async fn get_process_info(&self, name: &str) -> Result<&ProcessInfo> {
+    /// The ecosystem file a process was started from. Processes started from
+    /// the command line have no definition besides their own config, so
+    /// there is nothing to re-read their env from.
+    fn definition_source(&self, name: &str) -> Result<String> {
         self.processes
             .get(name)
-            .map(|p| &p.info)
-            .ok_or_else(|| RpmError::ProcessNotFound(name.to_string()))
+            .ok_or_else(|| RpmError::ProcessNotFound(name.to_string()))?
+            .info
+            .config
+            .source
+            .clone()
+            .ok_or_else(|| {
+                RpmError::Config(format!(
+                    "Process '{}' wasn't started from an ecosystem file, so there is no env to re-read; \
+                     use `rpm update {} --env KEY=VALUE` to change it",
+                    name, name
+                ))
+            })
     }
 
-    pub async fn get_logs(&self, name: &str, lines: usize) -> Result<Vec<String>> {
-        if let Some(process) = self.processes.get(name) {
-            let log_count = process.log_buffer.len();
-            let start = if log_count > lines { log_count - lines } else { 0 };
-            Ok(process.log_buffer[start..].to_vec())
+    /// Stops a process and cleans up after it before forgetting it.
+    pub async fn delete_process(&mut self, name: &str) -> Result<()> {
+        if let Some(mut process) = self.processes.shift_remove(name) {
+            process.stop().await?;
+            process.cleanup();
+            self.save_state().await?;
+            Ok(())
         } else {
             Err(RpmError::ProcessNotFound(name.to_string()))
         }
     }
 
+    pub async fn list_processes(&self) -> Vec<ProcessInfo> {
+        self.processes.values().map(|p| p.snapshot()).collect()
+    }
+
+    pub fn list_summaries(&self) -> Vec<ProcessSummary> {
+        self.processes.values().map(|p| p.summary()).collect()
+    }
+
+    pub async fn get_process_info(&self, name: &str) -> Result<ProcessInfo> {
+        let process = self.processes
+            .get(name)
+            .ok_or_else(|| RpmError::ProcessNotFound(name.to_string()))?;
+        let mut info = process.snapshot();
+        info.start_env = process.start_env.as_ref().map(mask_secrets);
+        // Never started here: show what a start would run.
+        if info.exec.is_none() {
+            info.exec = info.config.resolved_env()
+                .and_then(|env| resolve_argv(&info.config, &env))
+                .ok();
+        }
+        info.config.env = mask_secrets(info.config.env.iter().map(|(key, value)| (key, value)));
+        for env in info.config.env_profiles.values_mut() {
+            *env = mask_secrets(env.iter().map(|(key, value)| (key, value)));
+        }
+        Ok(info)
+    }
+
+    /// Returns the last `lines` log lines of a process, oldest first.
+    ///
+    /// Recent output is served from the in-memory buffer. When more lines are
+    /// requested than the buffer holds, the process's log files (including
+    /// rotated segments) are read instead, interleaving stdout and stderr by
+    /// timestamp; lines written within the same second keep their per-stream
+    /// order. If the files can't be read, whatever the buffer holds is returned.
+    /// Requesting zero lines returns an empty list.
+    pub async fn get_logs(&self, name: &str, lines: usize) -> Result<Vec<logs::LogLine>> {
+        let process = self
+            .processes
+            .get(name)
+            .ok_or_else(|| RpmError::ProcessNotFound(name.to_string()))?;
+
+        if lines > MAX_LOG_LINES {
+            tracing::warn!(
+                "Requested {} log lines for '{}', returning the last {}",
+                lines, name, MAX_LOG_LINES
+            );
+        }
+        let lines = lines.min(MAX_LOG_LINES);
+
+        let buffered: Vec<logs::LogLine> = {
+            let buffer = process.log_buffer.lock().unwrap();
+            let start = buffer.len().saturating_sub(lines);
+            buffer.iter().skip(start).cloned().collect()
+        };
+
+        if buffered.len() >= lines {
+            return Ok(buffered);
+        }
+
+        match Self::read_log_files(&process.info, lines) {
+            Ok(from_files) if from_files.len() > buffered.len() => Ok(from_files),
+            Ok(_) => Ok(buffered),
+            Err(e) => {
+                tracing::warn!("Failed to read log files for '{}': {}", name, e);
+                Ok(buffered)
+            }
+        }
+    }
+
+    fn read_log_files(info: &ProcessInfo, lines: usize) -> Result<Vec<logs::LogLine>> {
+        let mut combined = Vec::new();
+        let files = [(&info.out_log_path, logs::LogStream::Stdout), (&info.err_log_path, logs::LogStream::Stderr)];
+        for (path, stream) in files {
+            let Some(path) = path else { continue };
+            let read = logs::read_log_file(std::path::Path::new(path), lines)?;
+            combined.extend(read.iter().map(|line| logs::LogLine::parse(line, stream)));
+        }
+
+        // Stable, so lines from the same second keep their file order.
+        combined.sort_by_key(|line| line.timestamp);
+        let start = combined.len().saturating_sub(lines);
+        Ok(combined.split_off(start))
+    }
+
     pub async fn monitor_processes(&mut self) -> Result<()> {
         let mut to_restart = Vec::new();
+        let sample_interval = Duration::from_secs(self.config.sample_interval.unwrap_or(0));
 
         for (name, process) in &mut self.processes {
-            process.check_status().await?;
-            
-            if process.should_restart() {
-                to_restart.push(name.clone());
+            let was_running = process.child.is_some();
+            process.check_status(sample_interval).await?;
+            let crashed = was_running && process.exited_unexpectedly;
+
+            if crashed {
+                // No receivers is fine; nobody is subscribed.
+                let _ = self.events.send(ProcessEvent::Crash(CrashEvent::new(process)));
+            }
+
+            // Exits, usage and warning thresholds are still tracked while
+            // paused; a crash is acted on once monitoring resumes.
+            if process.monitored && process.should_restart() {
+                let window = Duration::from_secs(self.config.restart_window);
+                let max_restarts = process.info.config.max_restarts.unwrap_or(self.config.max_restarts_in_window);
+                if process.crash_looping(window, max_restarts) {
+                    tracing::warn!(
+                        "Process '{}' crashed {} times within {}s; not restarting it again",
+                        name,
+                        process.crash_restarts.len() + 1,
+                        self.config.restart_window
+                    );
+                    // Stays this way until restarted by hand.
+                    process.info.status = ProcessStatus::CrashLooped;
+                    process.exited_unexpectedly = false;
+                } else {
+                    to_restart.push((name.clone(), RestartReason::Crash { exit_code: process.last_exit_code }));
+                }
+            }
+
+            // Usage is still sampled during warmup, just not acted on.
+            if process.warming_up() {
+                continue;
+            }
+
+            for event in process.check_thresholds() {
+                if event.exceeded {
+                    tracing::warn!(
+                        "Process '{}' {} usage {:.1} is over its warning threshold {}",
+                        name, event.resource, event.usage, event.threshold
+                    );
+                }
+                let _ = self.events.send(ProcessEvent::Threshold(event));
             }
 
-            if let Some(max_memory) = process.info.config.max_memory {
+            if !process.monitored {
+                continue;
+            }
+
+            if let Some(max_memory) = process.info.config.max_memory.filter(|_| !process.limits_enforced()) {
                 let memory_mb = process.info.memory_usage / 1024 / 1024;
                 if memory_mb > max_memory {
                     tracing::warn!("Process '{}' exceeded memory limit: {}MB > {}MB", 
                                    name, memory_mb, max_memory);
-                    to_restart.push(name.clone());
+                    to_restart.push((name.clone(), RestartReason::MemoryLimit));
                 }
             }
         }
 
-        for name in to_restart {
-            tracing::info!("Auto-restarting process '{}'", name);
-            if let Err(e) = self.restart_process(&name).await {
+        for (name, reason) in to_restart {
+            tracing::info!("Auto-restarting process '{}' ({})", name, reason);
+            if let Err(e) = self.restart_process(&name, None, reason).await {
                 tracing::error!("Failed to restart process '{}': {}", name, e);
             }
         }
@@ -349,39 +1616,301 @@ impl ProcessManager {
         self.config.save_processes(&self.processes).await
     }
 
-    pub async fn load_state(&mut self) -> Result<()> {
-        if let Ok(processes) = self.config.load_processes().await {
-            self.processes = processes;
+    /// Restores saved processes that aren't already managed and starts those
+    /// with autostart enabled. Processes the daemon already knows by name are
+    /// left alone, so running children keep their handles. Returns how many
+    /// processes were restored.
+    pub async fn resurrect(&mut self) -> Result<usize> {
+        let saved = self.config.load_processes().await?;
+        let mut restored = 0;
+
+        for (name, mut process) in saved {
+            if self.processes.contains_key(&name) {
+                tracing::info!("Skipping resurrect of '{}': already managed", name);
+                continue;
+            }
+            if self.processes.values().any(|live| live.info.id == process.info.id) {
+                process.info.id = self.next_free_id();
+            }
+            if process.info.config.autostart {
+                match process.start().await {
+                    Ok(()) => Self::attach_watcher(&self.watch_tx, &mut process),
+                    Err(e) => tracing::error!("Failed to resurrect process '{}': {}", name, e),
+                }
+            }
+            self.processes.insert(name, process);
+            restored += 1;
+        }
+        Ok(restored)
+    }
+
+    /// Reopens every log file the daemon writes to, first rotating each
+    /// process's logs when `rotate` is set.
+    pub fn reload_logs(&self, rotate: bool) -> Result<()> {
+        let mut pool = logs::file_pool().lock().unwrap();
+        if rotate {
+            for process in self.processes.values() {
+                for path in [&process.info.out_log_path, &process.info.err_log_path].into_iter().flatten() {
+                    pool.rotate_now(std::path::Path::new(path))?;
+                }
+            }
         }
+        pool.reopen_all();
+        Ok(())
+    }
+
+    /// Subscribes to a process's output as it is produced, returning up to
+    /// `backlog` buffered lines that precede the first line received. No line
+    /// is both in the backlog and received, and none falls between them.
+    pub fn subscribe_logs(
+        &self,
+        name: &str,
+        backlog: usize,
+    ) -> Result<(Vec<logs::LogLine>, tokio::sync::broadcast::Receiver<logs::LogLine>)> {
+        let process = self
+            .processes
+            .get(name)
+            .ok_or_else(|| RpmError::ProcessNotFound(name.to_string()))?;
+
+        // Lines are buffered and broadcast under the buffer lock.
+        let buffer = process.log_buffer.lock().unwrap();
+        let receiver = process.log_channel.subscribe();
+        let start = buffer.len().saturating_sub(backlog);
+        Ok((buffer.iter().skip(start).cloned().collect(), receiver))
+    }
+
+    pub async fn send_input(&mut self, name: &str, text: &str) -> Result<()> {
+        self.processes
+            .get_mut(name)
+            .ok_or_else(|| RpmError::ProcessNotFound(name.to_string()))?
+            .send_input(text)
+            .await
+    }
+
+    /// Pauses or resumes auto-restarts and memory limit checks for a process,
+    /// leaving the process itself running.
+    pub fn set_monitored(&mut self, name: &str, monitored: bool) -> Result<()> {
+        let process = self
+            .processes
+            .get_mut(name)
+            .ok_or_else(|| RpmError::ProcessNotFound(name.to_string()))?;
+        process.monitored = monitored;
         Ok(())
     }
+
+    pub async fn set_autostart(&mut self, name: &str, enabled: bool) -> Result<()> {
+        let process = self
+            .processes
+            .get_mut(name)
+            .ok_or_else(|| RpmError::ProcessNotFound(name.to_string()))?;
+        process.info.config.autostart = enabled;
+        self.save_state().await
+    }
+}
+
+/// Whether `/proc` can be read, checked once: minimal containers and
+/// non-Linux Unixes don't have it, and every sample would fail the same way.
+#[cfg(unix)]
+fn proc_available() -> bool {
+    static AVAILABLE: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        let available = std::path::Path::new("/proc/self/statm").exists();
+        if !available {
+            tracing::warn!("/proc is not available; process CPU and memory stats will be shown as unavailable");
+        }
+        available
+    })
+}
+
+/// Polls until nothing holds `port`, by binding it ourselves. Returns false if
+/// it is still taken after `timeout`. Errors other than "in use" (such as a
+/// privileged port) count as free, since there is nothing to wait for.
+async fn wait_for_port_free(port: u16, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match tokio::net::TcpListener::bind(("0.0.0.0", port)).await {
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => {}
+            _ => return true,
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// Whether CPU and memory can be sampled on this system at all.
+fn stats_supported() -> bool {
+    #[cfg(unix)]
+    {
+        proc_available() || cfg!(feature = "sysinfo")
+    }
+    #[cfg(windows)]
+    {
+        true
+    }
 }
 
+/// Reads memory from `/proc/<pid>/statm` and CPU time from `/proc/<pid>/stat`.
+/// CPU usage is the share of one core used since `previous`, so the first
+/// sample after a start reports 0.
 #[cfg(unix)]
-fn get_process_usage_unix(pid: u32) -> Result<(f64, u64)> {
+fn get_process_usage_unix(pid: u32, previous: &mut Option<(u64, Instant)>) -> Result<(f64, u64)> {
     use std::fs;
     
-    let stat_path = format!("/proc/{}/stat", pid);
     let statm_path = format!("/proc/{}/statm", pid);
     
-    let stat_content = fs::read_to_string(stat_path)
-        .map_err(|e| RpmError::Process(format!("Failed to read stat: {}", e)))?;
     let statm_content = fs::read_to_string(statm_path)
         .map_err(|e| RpmError::Process(format!("Failed to read statm: {}", e)))?;
     
-    let stat_parts: Vec<&str> = stat_content.split_whitespace().collect();
     let memory_pages: u64 = statm_content.split_whitespace()
         .nth(1)
         .and_then(|s| s.parse().ok())
         .unwrap_or(0);
     
-    let cpu_usage = 0.0; // Simplified - would need more complex calculation
+    let stat_content = fs::read_to_string(format!("/proc/{}/stat", pid))
+        .map_err(|e| RpmError::Process(format!("Failed to read stat: {}", e)))?;
+    // The command name in field 2 may contain spaces, so count fields from
+    // its closing parenthesis; utime and stime are fields 14 and 15.
+    let cpu_ticks: u64 = stat_content
+        .rsplit_once(')')
+        .map(|(_, fields)| {
+            fields
+                .split_whitespace()
+                .skip(11)
+                .take(2)
+                .filter_map(|ticks| ticks.parse::<u64>().ok())
+                .sum()
+        })
+        .unwrap_or(0);
+
+    let now = Instant::now();
+    let cpu_usage = match previous.replace((cpu_ticks, now)) {
+        Some((last_ticks, last_at)) => {
+            let ticks_per_sec = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as f64;
+            let elapsed = now.duration_since(last_at).as_secs_f64();
+            if elapsed > 0.0 {
+                cpu_ticks.saturating_sub(last_ticks) as f64 / ticks_per_sec / elapsed * 100.0
+            } else {
+                0.0
+            }
+        }
+        None => 0.0,
+    };
     let memory_usage = memory_pages * 4096; // Assuming 4KB pages
     
     Ok((cpu_usage, memory_usage))
 }
 
-#[cfg(windows)]
+#[cfg(feature = "sysinfo")]
+fn sample_usage_fallback(pid: u32) -> Option<(f64, u64)> {
+    use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+    // CPU usage is measured between refreshes, so one System is kept for the
+    // daemon's lifetime.
+    static SYSTEM: std::sync::OnceLock<std::sync::Mutex<System>> = std::sync::OnceLock::new();
+    let mut system = SYSTEM.get_or_init(|| std::sync::Mutex::new(System::new())).lock().unwrap();
+
+    let pid = Pid::from_u32(pid);
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing().with_cpu().with_memory(),
+    );
+    system
+        .process(pid)
+        .map(|process| (f64::from(process.cpu_usage()), process.memory()))
+}
+
+#[cfg(all(windows, not(feature = "sysinfo")))]
+fn sample_usage_fallback(pid: u32) -> Option<(f64, u64)> {
+    get_process_usage_windows(pid).ok()
+}
+
+#[cfg(all(unix, not(feature = "sysinfo")))]
+fn sample_usage_fallback(_pid: u32) -> Option<(f64, u64)> {
+    None
+}
+
+/// Field 22 of `/proc/<pid>/stat`: when the process started, in clock ticks
+/// after boot. Fields are counted from the command name's closing parenthesis
+/// since the name may contain spaces.
+#[cfg(target_os = "linux")]
+fn get_start_ticks_linux(pid: u32) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(19)?.parse().ok()
+}
+
+#[cfg(target_os = "linux")]
+fn get_thread_count_linux(pid: u32) -> Option<u32> {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("Threads:"))
+        .and_then(|count| count.trim().parse().ok())
+}
+
+/// Every process below `pid` in the process tree, found through the parent
+/// pid field of `/proc/<pid>/stat`.
+#[cfg(target_os = "linux")]
+fn descendants_linux(pid: u32) -> Vec<u32> {
+    let mut parents: Vec<(u32, u32)> = Vec::new();
+    for entry in std::fs::read_dir("/proc").into_iter().flatten().flatten() {
+        let Some(child) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        // The command name in parentheses may contain spaces, so fields are
+        // counted from the closing one: state, then ppid.
+        let ppid = std::fs::read_to_string(entry.path().join("stat")).ok().and_then(|stat| {
+            stat.rsplit_once(')')?.1.split_whitespace().nth(1)?.parse::<u32>().ok()
+        });
+        if let Some(ppid) = ppid {
+            parents.push((child, ppid));
+        }
+    }
+
+    let mut found = vec![pid];
+    let mut index = 0;
+    while index < found.len() {
+        let parent = found[index];
+        found.extend(parents.iter().filter(|(_, ppid)| *ppid == parent).map(|(child, _)| *child));
+        index += 1;
+    }
+    found.split_off(1)
+}
+
+/// Reads `read_bytes` and `write_bytes` from `/proc/<pid>/io` and returns
+/// them as bytes per second since `previous`; the first sample reports 0.
+/// `None` when the file can't be read (it needs ptrace access to the process).
+#[cfg(target_os = "linux")]
+fn get_disk_io_linux(pid: u32, previous: &mut Option<(u64, u64, Instant)>) -> Option<(u64, u64)> {
+    let io = std::fs::read_to_string(format!("/proc/{}/io", pid)).ok()?;
+    let field = |name: &str| {
+        io.lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+    let (read, write) = (field("read_bytes:")?, field("write_bytes:")?);
+
+    let now = Instant::now();
+    Some(match previous.replace((read, write, now)) {
+        Some((last_read, last_write, last_at)) => {
+            let elapsed = now.duration_since(last_at).as_secs_f64();
+            if elapsed > 0.0 {
+                (
+                    (read.saturating_sub(last_read) as f64 / elapsed) as u64,
+                    (write.saturating_sub(last_write) as f64 / elapsed) as u64,
+                )
+            } else {
+                (0, 0)
+            }
+        }
+        None => (0, 0),
+    })
+}
+
+#[cfg(all(windows, not(feature = "sysinfo")))]
 fn get_process_usage_windows(pid: u32) -> Result<(f64, u64)> {
     use winapi::um::processthreadsapi::OpenProcess;
     use winapi::um::psapi::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
@@ -406,6 +1935,127 @@ fn get_process_usage_windows(pid: u32) -> Result<(f64, u64)> {
     Ok((0.0, 0))
 }
 
+/// The program and arguments a process is spawned with: `--argv` as given,
+/// else `sh -c <command>` in shell mode, else the command's words, with the
+/// program resolved against the process's `PATH`.
+pub fn resolve_argv(config: &ProcessConfig, env: &[(String, String)]) -> Result<Vec<String>> {
+    #[cfg(unix)]
+    {
+        if config.shell && config.args.is_none() {
+            return Ok(vec!["sh".to_string(), "-c".to_string(), config.command.clone()]);
+        }
+
+        let parts: Vec<&str> = match &config.args {
+            Some(args) => args.iter().map(String::as_str).collect(),
+            None => config.command.split_whitespace().collect(),
+        };
+        if parts.is_empty() {
+            return Err(RpmError::Process("Empty command".to_string()));
+        }
+
+        // Resolve up front: a missing program would otherwise only show up
+        // as a spawn error, with no hint of which lookup failed.
+        let path_var = env
+            .iter()
+            .find(|(key, _)| key == "PATH")
+            .map(|(_, value)| value.clone())
+            .or_else(|| std::env::var("PATH").ok())
+            .unwrap_or_default();
+        let executable = find_executable_in_path(parts[0], config.cwd.as_deref(), &path_var)
+            .ok_or_else(|| RpmError::Process(format!("command not found: {}", parts[0])))?;
+
+        let mut argv = vec![executable.display().to_string()];
+        argv.extend(parts[1..].iter().map(|part| part.to_string()));
+        Ok(argv)
+    }
+
+    #[cfg(windows)]
+    {
+        let _ = env;
+        let parts: Vec<&str> = match &config.args {
+            Some(args) => args.iter().map(String::as_str).collect(),
+            None => config.command.split_whitespace().collect(),
+        };
+        if parts.is_empty() {
+            return Err(RpmError::Process("Empty command".to_string()));
+        }
+        
+        // Try to find the executable in PATH if it's not already a full path
+        let executable = if std::path::Path::new(parts[0]).exists() {
+            parts[0].to_string()
+        } else {
+            match find_executable_in_path(parts[0]) {
+                Some(path) => {
+                    tracing::info!("Found executable '{}' at path: {}", parts[0], path);
+                    path
+                }
+                None => {
+                    return Err(RpmError::Process(format!("command not found: {}", parts[0])));
+                }
+            }
+        };
+        
+        let mut argv = vec![executable];
+        argv.extend(parts[1..].iter().map(|part| part.to_string()));
+        Ok(argv)
+    }
+}
+
+/// Replaces the values of variables whose names suggest credentials, so
+/// `rpm show` can print an environment without leaking them. Names are
+/// matched by `_`-separated word, so `API_KEY` is masked but `MONKEY` isn't.
+fn mask_secrets<'a, C>(env: impl IntoIterator<Item = (&'a String, &'a String)>) -> C
+where
+    C: FromIterator<(String, String)>,
+{
+    const SECRET_WORDS: [&str; 11] = [
+        "SECRET", "SECRETS", "TOKEN", "PASSWORD", "PASSWD", "KEY", "KEYS", "APIKEY", "CREDENTIAL", "CREDENTIALS", "AUTH",
+    ];
+    env.into_iter()
+        .map(|(key, value)| {
+            let upper = key.to_ascii_uppercase();
+            if upper.split('_').any(|word| SECRET_WORDS.contains(&word)) && !value.is_empty() {
+                (key.clone(), "********".to_string())
+            } else {
+                (key.clone(), value.clone())
+            }
+        })
+        .collect()
+}
+
+/// A random number in `[0, 1)`, good enough for jitter. `RandomState` is
+/// seeded from the OS, so this needs no RNG dependency.
+fn random_fraction() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+
+    let bits = std::collections::hash_map::RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(unix)]
+fn find_executable_in_path(name: &str, cwd: Option<&str>, path_var: &str) -> Option<std::path::PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::{Path, PathBuf};
+
+    let is_executable = |path: &Path| {
+        path.metadata()
+            .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    };
+
+    if name.contains('/') {
+        let path = match cwd {
+            Some(cwd) => Path::new(cwd).join(name),
+            None => PathBuf::from(name),
+        };
+        return is_executable(&path).then_some(path);
+    }
+
+    std::env::split_paths(path_var)
+        .map(|dir| dir.join(name))
+        .find(|path| is_executable(path))
+}
+
 #[cfg(windows)]
 fn find_executable_in_path(name: &str) -> Option<String> {
     use std::env;
@@ -0,0 +1,47 @@
+use crate::{Result, RpmError};
+use serde_json::Value;
+
+/// Renders a `docker ps --format` style template such as
+/// `{{.name}}\t{{.pid}}` against a JSON value. Fields are looked up by
+/// dotted path (`{{.config.group}}`); strings are inserted without quotes,
+/// missing and null values as nothing, and objects and arrays as JSON.
+/// `\t` and `\n` in the template become a tab and a newline.
+pub fn render(template: &str, value: &Value) -> Result<String> {
+    let template = template.replace("\\t", "\t").replace("\\n", "\n");
+    let mut output = String::new();
+    let mut rest = template.as_str();
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| RpmError::Config(format!("Unclosed '{{{{' in format '{}'", template)))?;
+        output.push_str(&render_field(after[..end].trim(), value)?);
+        rest = &after[end + 2..];
+    }
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+fn render_field(field: &str, value: &Value) -> Result<String> {
+    let path = field
+        .strip_prefix('.')
+        .ok_or_else(|| RpmError::Config(format!("Invalid field '{}' in format, expected e.g. {{{{.name}}}}", field)))?;
+
+    let mut current = value;
+    if !path.is_empty() {
+        for key in path.split('.') {
+            current = current
+                .get(key)
+                .ok_or_else(|| RpmError::Config(format!("Unknown field '.{}' in format", path)))?;
+        }
+    }
+
+    Ok(match current {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    })
+}
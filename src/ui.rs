@@ -1,95 +1,392 @@
 use colored::*;
 use comfy_table::presets::UTF8_FULL;
 use comfy_table::{Cell, Color, ContentArrangement, Table};
-use crate::process::{ProcessInfo, ProcessStatus};
+use crate::process::{ProcessInfo, ProcessStatus, ProcessSummary, Resource};
+use std::cmp::Reverse;
 use std::time::Duration;
 
+/// Orders process listings. Resource columns sort highest first.
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum SortKey {
+    Name,
+    Cpu,
+    Memory,
+    Restarts,
+    Uptime,
+}
+
+pub fn sort_processes<P: ProcessRow>(processes: &mut [P], key: SortKey) {
+    match key {
+        SortKey::Name => processes.sort_by(|a, b| a.name().cmp(b.name())),
+        SortKey::Cpu => processes.sort_by(|a, b| b.cpu_usage().total_cmp(&a.cpu_usage())),
+        SortKey::Memory => processes.sort_by_key(|p| Reverse(p.memory_usage())),
+        SortKey::Restarts => processes.sort_by_key(|p| Reverse(p.restarts())),
+        SortKey::Uptime => processes.sort_by_key(|p| Reverse(p.uptime())),
+    }
+}
+
+/// The fields listings sort by and color their usage cells with, shared by
+/// full process info and the summaries `rpm list` fetches.
+pub trait ProcessRow {
+    fn name(&self) -> &str;
+    fn cpu_usage(&self) -> f64;
+    fn memory_usage(&self) -> u64;
+    fn restarts(&self) -> u32;
+    fn uptime(&self) -> Option<u64>;
+    fn stats_unavailable(&self) -> bool;
+    fn warnings(&self) -> &[Resource];
+}
+
+impl ProcessRow for ProcessInfo {
+    fn name(&self) -> &str { &self.name }
+    fn cpu_usage(&self) -> f64 { self.cpu_usage }
+    fn memory_usage(&self) -> u64 { self.memory_usage }
+    fn restarts(&self) -> u32 { self.restarts }
+    fn uptime(&self) -> Option<u64> { self.uptime }
+    fn stats_unavailable(&self) -> bool { self.stats_unavailable }
+    fn warnings(&self) -> &[Resource] { &self.warnings }
+}
+
+impl ProcessRow for ProcessSummary {
+    fn name(&self) -> &str { &self.name }
+    fn cpu_usage(&self) -> f64 { self.cpu_usage }
+    fn memory_usage(&self) -> u64 { self.memory_usage }
+    fn restarts(&self) -> u32 { self.restarts }
+    fn uptime(&self) -> Option<u64> { self.uptime }
+    fn stats_unavailable(&self) -> bool { self.stats_unavailable }
+    fn warnings(&self) -> &[Resource] { &self.warnings }
+}
+
+/// How CPU usage is displayed. The daemon always measures it as a share of
+/// one core, as top does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CpuMode {
+    /// Percent of one core; a multithreaded process can go over 100%.
+    #[default]
+    PerCore,
+    /// Percent of all cores together, 0-100%.
+    WholeMachine,
+}
+
+static CPU_MODE: std::sync::OnceLock<CpuMode> = std::sync::OnceLock::new();
+
+/// Sets the CPU display mode for the rest of the run. Only the first call counts.
+pub fn set_cpu_mode(mode: CpuMode) {
+    let _ = CPU_MODE.set(mode);
+}
+
+pub fn cpu_mode() -> CpuMode {
+    CPU_MODE.get().copied().unwrap_or_default()
+}
+
+/// Converts a per-core reading to the configured display mode.
+pub fn display_cpu(per_core: f64) -> f64 {
+    match cpu_mode() {
+        CpuMode::PerCore => per_core,
+        CpuMode::WholeMachine => {
+            let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+            per_core / cores as f64
+        }
+    }
+}
+
 pub struct TableFormatter;
 
 impl TableFormatter {
-    pub fn format_process_list(processes: &[&ProcessInfo]) -> String {
+    /// The process table, with a DESCRIPTION column when `show_description`
+    /// is set. Running processes restarted after `restarted_since` are marked
+    /// in the STATUS column.
+    pub fn format_process_list(
+        processes: &[&ProcessSummary],
+        show_description: bool,
+        restarted_since: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> String {
         if processes.is_empty() {
             return "No processes running".bright_yellow().to_string();
         }
 
+        let mut header = vec![
+            Cell::new("NAME").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("ID").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("STATUS").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("CPU").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("MEMORY").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("RESTARTS").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("UPTIME").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
+        ];
+        if show_description {
+            header.push(Cell::new("DESCRIPTION").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold));
+        }
+
         let mut table = Table::new();
         table
             .load_preset(UTF8_FULL)
             .set_content_arrangement(ContentArrangement::Dynamic)
-            .set_header(vec![
-                Cell::new("NAME").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
-                Cell::new("ID").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
-                Cell::new("STATUS").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
-                Cell::new("CPU").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
-                Cell::new("MEMORY").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
-                Cell::new("RESTARTS").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
-                Cell::new("UPTIME").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
-            ]);
+            .set_header(header);
 
         for process in processes {
-            let status_cell = Self::format_status_cell(&process.status);
-            let cpu_cell = Cell::new(format!("{:.1}%", process.cpu_usage))
-                .fg(Self::get_cpu_color(process.cpu_usage));
-            let memory_cell = Cell::new(Self::format_memory(process.memory_usage))
-                .fg(Self::get_memory_color(process.memory_usage));
-            let uptime_cell = Cell::new(Self::format_duration_since(process.started_at));
+            let just_restarted = process.status == ProcessStatus::Running
+                && restarted_since.is_some_and(|since| process.last_restart_at.is_some_and(|at| at > since));
+            let status_cell = if just_restarted {
+                Cell::new("↻  just restarted").fg(Color::Yellow).add_attribute(comfy_table::Attribute::Bold)
+            } else if process.monitoring_paused {
+                Cell::new(format!("{} (paused)", Self::format_status_cell(&process.status).content())).fg(Color::Yellow)
+            } else {
+                Self::format_status_cell(&process.status)
+            };
+            let cpu_cell = Self::format_cpu_cell(*process);
+            let memory_cell = Self::format_memory_cell(*process);
+            let uptime_cell = Cell::new(Self::format_uptime(process.uptime));
             
-            table.add_row(vec![
+            let mut row = vec![
                 Cell::new(&process.name).fg(Color::White),
-                Cell::new(&process.id[..8]).fg(Color::DarkGrey), // Show only first 8 chars of UUID
+                Cell::new(process.id).fg(Color::DarkGrey),
                 status_cell,
                 cpu_cell,
                 memory_cell,
                 Cell::new(process.restarts.to_string()).fg(if process.restarts > 0 { Color::Yellow } else { Color::DarkGrey }),
                 uptime_cell,
+            ];
+            if show_description {
+                row.push(Cell::new(process.comment.as_deref().unwrap_or("")).fg(Color::DarkGrey));
+            }
+            table.add_row(row);
+        }
+
+        table.to_string()
+    }
+
+    /// A compact resource table for `rpm top`.
+    pub fn format_resource_snapshot(processes: &[&ProcessInfo]) -> String {
+        if processes.is_empty() {
+            return "No processes running".bright_yellow().to_string();
+        }
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec![
+                Cell::new("NAME").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
+                Cell::new("PID").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
+                Cell::new("CPU").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
+                Cell::new("MEMORY").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
+                Cell::new("THREADS").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
+                Cell::new("DISK READ").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
+                Cell::new("DISK WRITE").fg(Color::Cyan).add_attribute(comfy_table::Attribute::Bold),
+            ]);
+
+        for process in processes {
+            let missing = || Cell::new("-").fg(Color::DarkGrey);
+            table.add_row(vec![
+                Cell::new(&process.name).fg(Color::White),
+                process.pid.map_or_else(missing, |pid| Cell::new(pid.to_string())),
+                Self::format_cpu_cell(*process),
+                Self::format_memory_cell(*process),
+                process.threads.map_or_else(missing, |threads| Cell::new(threads.to_string())),
+                process.disk_read.map_or_else(missing, |rate| Cell::new(Self::format_rate(rate))),
+                process.disk_write.map_or_else(missing, |rate| Cell::new(Self::format_rate(rate))),
             ]);
         }
 
         table.to_string()
     }
 
-    pub fn format_process_details(process: &ProcessInfo) -> String {
+    pub fn format_process_details(process: &ProcessInfo, local: bool) -> String {
         let mut output = String::new();
         
         output.push_str(&format!("{}\n", "Process Information".bright_cyan().bold()));
         output.push_str(&format!("{}────────────────────\n", "".bright_cyan()));
         
         output.push_str(&format!("{:<12} {}\n", "Name:".bright_white(), process.name.bright_yellow()));
-        output.push_str(&format!("{:<12} {}\n", "ID:".bright_white(), process.id.bright_blue()));
+        output.push_str(&format!("{:<12} {}\n", "ID:".bright_white(), process.id.to_string().bright_blue()));
+        if let Some(group) = &process.config.group {
+            output.push_str(&format!("{:<12} {}\n", "Group:".bright_white(), group.bright_blue()));
+        }
+        if let Some(description) = &process.config.comment {
+            output.push_str(&format!("{:<12} {}\n", "Description:".bright_white(), description.white()));
+        }
+        if !process.config.metadata.is_empty() {
+            let metadata: Vec<String> =
+                process.config.metadata.iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+            output.push_str(&format!("{:<12} {}\n", "Metadata:".bright_white(), metadata.join(", ").white()));
+        }
         output.push_str(&format!("{:<12} {}\n", "Status:".bright_white(), Self::format_status_text(&process.status)));
+        if process.monitoring_paused {
+            output.push_str(&format!("{:<12} {}\n", "Monitoring:".bright_white(), "paused (rpm resume to re-enable)".yellow()));
+        }
+        if let Some(error) = &process.start_error {
+            output.push_str(&format!("{:<12} {}\n", "Start error:".bright_white(), error.red()));
+        }
         output.push_str(&format!("{:<12} {}\n", "PID:".bright_white(), 
             process.pid.map_or("N/A".dimmed().to_string(), |p| p.to_string().bright_green().to_string())));
-        output.push_str(&format!("{:<12} {}\n", "CPU:".bright_white(), 
-            format!("{:.1}%", process.cpu_usage).color(Self::get_cpu_color_name(process.cpu_usage))));
-        output.push_str(&format!("{:<12} {}\n", "Memory:".bright_white(), 
-            Self::format_memory(process.memory_usage).color(Self::get_memory_color_name(process.memory_usage))));
+        if process.stats_unavailable {
+            output.push_str(&format!("{:<12} {}\n", "CPU:".bright_white(), "unavailable".dimmed()));
+            output.push_str(&format!("{:<12} {}\n", "Memory:".bright_white(), "unavailable".dimmed()));
+        } else {
+            let cpu = display_cpu(process.cpu_usage);
+            let mut cpu_text = format!("{:.1}%", cpu).color(Self::get_cpu_color_name(cpu)).to_string();
+            if let Some(limit) = process.config.max_cpu {
+                cpu_text.push_str(&format!(" / {:.0}% limit", display_cpu(f64::from(limit))).dimmed().to_string());
+            }
+            output.push_str(&format!("{:<12} {}\n", "CPU:".bright_white(), cpu_text));
+            let mut memory_text = Self::format_memory(process.memory_usage)
+                .color(Self::get_memory_color_name(process.memory_usage))
+                .to_string();
+            if let Some(limit_mb) = process.config.max_memory {
+                let used = process.memory_usage as f64 / (limit_mb as f64 * 1024.0 * 1024.0) * 100.0;
+                memory_text.push_str(&format!(" / {}MB limit ({:.0}%)", limit_mb, used).dimmed().to_string());
+            }
+            output.push_str(&format!("{:<12} {}\n", "Memory:".bright_white(), memory_text));
+        }
+        let thresholds: Vec<String> = [
+            process.config.warn_cpu.map(|percent| (Resource::Cpu, format!("cpu over {}%", percent))),
+            process.config.warn_memory.map(|mb| (Resource::Memory, format!("memory over {}MB", mb))),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|(resource, text)| {
+            if process.warnings.contains(&resource) {
+                format!("{} ⚠", text).bright_yellow().to_string()
+            } else {
+                text.dimmed().to_string()
+            }
+        })
+        .collect();
+        if !thresholds.is_empty() {
+            output.push_str(&format!("{:<12} {}\n", "Warn at:".bright_white(), thresholds.join(", ")));
+        }
+        if let (Some(read), Some(write)) = (process.disk_read, process.disk_write) {
+            output.push_str(&format!("{:<12} {}\n", "Disk I/O:".bright_white(),
+                format!("{} read, {} written", Self::format_rate(read), Self::format_rate(write)).bright_white()));
+        }
         output.push_str(&format!("{:<12} {}\n", "Command:".bright_white(), process.command.bright_white()));
+        if let Some(exec) = &process.exec {
+            let argv: Vec<String> = exec.iter().map(|arg| shell_quote(arg)).collect();
+            output.push_str(&format!("{:<12} {}\n", "Exec:".bright_white(), argv.join(" ").white()));
+        }
         output.push_str(&format!("{:<12} {}\n", "Started:".bright_white(), 
-            process.started_at.format("%Y-%m-%d %H:%M:%S UTC").to_string().bright_magenta()));
-        output.push_str(&format!("{:<12} {}\n", "Restarts:".bright_white(), 
+            format_timestamp(process.started_at, local).bright_magenta()));
+        output.push_str(&format!("{:<12} {}", "Restarts:".bright_white(), 
             process.restarts.to_string().color(if process.restarts > 0 { "yellow" } else { "bright_black" })));
-        output.push_str(&format!("{:<12} {}\n", "Uptime:".bright_white(), Self::format_duration_since(process.started_at).bright_green()));
+        if let Some(reason) = &process.last_restart_reason {
+            let last = match process.last_restart_at {
+                Some(at) => format!("(last: {}, {})", reason, format_timestamp(at, local)),
+                None => format!("(last: {})", reason),
+            };
+            output.push_str(&format!(" {}", last.dimmed()));
+        }
+        output.push('\n');
+        output.push_str(&format!("{:<12} {}\n", "Crashes:".bright_white(),
+            process.crashes.to_string().color(if process.crashes > 0 { "red" } else { "bright_black" })));
+        output.push_str(&format!("{:<12} {}\n", "Uptime:".bright_white(), Self::format_uptime(process.uptime).bright_green()));
+        let mut restart_delay = format!("after {}ms", process.config.restart_delay().as_millis());
+        if process.config.restart_delay.is_none() {
+            restart_delay.push_str(" (built-in default)");
+        }
+        if process.config.backoff {
+            restart_delay.push_str(", doubling per crash");
+        }
+        if let Some(jitter) = process.config.restart_jitter.filter(|jitter| *jitter > 0.0) {
+            restart_delay.push_str(&format!(" + up to {:.0}% jitter", jitter.min(1.0) * 100.0));
+        }
+        if let Some(max_restarts) = process.config.max_restarts {
+            restart_delay.push_str(&format!(", at most {} crash restarts", max_restarts));
+        }
+        if let Some(min_uptime) = process.config.min_uptime {
+            restart_delay.push_str(&format!(", stable after {}s", min_uptime));
+        }
+        let customized = process.config.restart_delay.is_some()
+            || process.config.restart_jitter.is_some()
+            || process.config.backoff
+            || process.config.max_restarts.is_some()
+            || process.config.min_uptime.is_some();
+        output.push_str(&format!("{:<12} {}\n", "Restart:".bright_white(),
+            if customized { restart_delay.white() } else { restart_delay.dimmed() }));
+        if let Some(port) = process.config.ready_port {
+            output.push_str(&format!("{:<12} {}\n", "Port:".bright_white(),
+                format!("{} (restarts wait for it to be free)", port).white()));
+        }
+        output.push_str(&format!("{:<12} {}\n", "Autostart:".bright_white(),
+            if process.config.autostart { "enabled".bright_green() } else { "disabled".bright_black() }));
         
         if let Some(cwd) = &process.config.cwd {
             output.push_str(&format!("{:<12} {}\n", "Directory:".bright_white(), cwd.bright_blue()));
         }
         
+        if let Some(path) = &process.out_log_path {
+            output.push_str(&format!("{:<12} {}\n", "Out log:".bright_white(), path.bright_blue()));
+        }
+
+        if let Some(path) = &process.err_log_path {
+            output.push_str(&format!("{:<12} {}\n", "Error log:".bright_white(), path.bright_blue()));
+        } else if process.config.merge_logs {
+            output.push_str(&format!("{:<12} {}\n", "Error log:".bright_white(), "merged into out log".dimmed()));
+        }
+
+        let config = &process.config;
+        let unset = || "none".dimmed().to_string();
+        output.push_str(&format!("\n{}\n", "Config".bright_cyan().bold()));
+        output.push_str(&format!("{}────────────────────\n", "".bright_cyan()));
+        output.push_str(&format!("{:<12} {}\n", "Instances:".bright_white(), config.instances.to_string().white()));
+        output.push_str(&format!("{:<12} {}\n", "Autorestart:".bright_white(),
+            if config.autorestart { "enabled".bright_green() } else { "disabled".bright_black() }));
+        output.push_str(&format!("{:<12} {}\n", "Max memory:".bright_white(),
+            config.max_memory.map_or_else(unset, |mb| format!("{}MB", mb).white().to_string())));
+        output.push_str(&format!("{:<12} {}\n", "Max CPU:".bright_white(),
+            config.max_cpu.map_or_else(unset, |percent| format!("{}% of one core (cgroups only)", percent).white().to_string())));
+        output.push_str(&format!("{:<12} {}\n", "Kill after:".bright_white(),
+            format!("{}s", config.kill_timeout).white()));
+        output.push_str(&format!("{:<12} {}\n", "Warmup:".bright_white(),
+            format!("{}s", config.warmup).white()));
+
         if !process.config.env.is_empty() {
-            output.push_str(&format!("{:<12}\n", "Environment:".bright_white()));
+            output.push_str(&format!("{}\n", "Environment (secrets masked):".bright_white()));
             for (key, value) in &process.config.env {
                 output.push_str(&format!("  {}: {}\n", key.bright_cyan(), value.white()));
             }
         }
 
+        if let Some(env) = &process.start_env {
+            output.push_str(&format!("{}\n", "Started with (secrets masked):".bright_white()));
+            for (key, value) in env {
+                output.push_str(&format!("  {}: {}\n", key.bright_cyan(), value.white()));
+            }
+        }
+
         output
     }
 
+    fn format_cpu_cell(process: &impl ProcessRow) -> Cell {
+        if process.stats_unavailable() {
+            return Cell::new("n/a").fg(Color::DarkGrey);
+        }
+        let cpu = display_cpu(process.cpu_usage());
+        if process.warnings().contains(&Resource::Cpu) {
+            return Cell::new(format!("{:.1}% ⚠", cpu)).fg(Color::Yellow);
+        }
+        Cell::new(format!("{:.1}%", cpu)).fg(Self::get_cpu_color(cpu))
+    }
+
+    fn format_memory_cell(process: &impl ProcessRow) -> Cell {
+        if process.stats_unavailable() {
+            return Cell::new("n/a").fg(Color::DarkGrey);
+        }
+        let memory = process.memory_usage();
+        if process.warnings().contains(&Resource::Memory) {
+            return Cell::new(format!("{} ⚠", Self::format_memory(memory))).fg(Color::Yellow);
+        }
+        Cell::new(Self::format_memory(memory)).fg(Self::get_memory_color(memory))
+    }
+
     fn format_status_cell(status: &ProcessStatus) -> Cell {
         match status {
             ProcessStatus::Running => Cell::new("●  running").fg(Color::Green),
             ProcessStatus::Stopped => Cell::new("○  stopped").fg(Color::Red),
             ProcessStatus::Errored => Cell::new("✕  errored").fg(Color::DarkRed),
             ProcessStatus::Restarting => Cell::new("↻  restarting").fg(Color::Yellow),
+            ProcessStatus::CrashLooped => Cell::new("✕  crash-looped").fg(Color::Magenta),
         }
     }
 
@@ -99,9 +396,12 @@ impl TableFormatter {
             ProcessStatus::Stopped => "○  stopped".bright_red(),
             ProcessStatus::Errored => "✕  errored".red(),
             ProcessStatus::Restarting => "↻  restarting".bright_yellow(),
+            ProcessStatus::CrashLooped => "✕  crash-looped".bright_magenta(),
         }
     }
 
+    /// Thresholds apply to the displayed value: in per-core mode red means
+    /// most of a core, in whole-machine mode most of the machine.
     fn get_cpu_color(cpu: f64) -> Color {
         match cpu {
             x if x > 80.0 => Color::Red,
@@ -149,14 +449,19 @@ impl TableFormatter {
         }
     }
 
-    fn format_duration_since(start: chrono::DateTime<chrono::Utc>) -> String {
-        let now = chrono::Utc::now();
-        let duration = now.signed_duration_since(start);
-        
-        if let Ok(std_duration) = duration.to_std() {
-            Self::format_duration(std_duration)
+    fn format_rate(bytes_per_sec: u64) -> String {
+        let kb = bytes_per_sec as f64 / 1024.0;
+        if kb >= 1024.0 {
+            format!("{:.1}MB/s", kb / 1024.0)
         } else {
-            "N/A".to_string()
+            format!("{:.1}KB/s", kb)
+        }
+    }
+
+    fn format_uptime(uptime: Option<u64>) -> String {
+        match uptime {
+            Some(seconds) => Self::format_duration(Duration::from_secs(seconds)),
+            None => "N/A".to_string(),
         }
     }
 
@@ -182,7 +487,17 @@ impl TableFormatter {
 pub struct ProgressIndicator;
 
 impl ProgressIndicator {
+    /// Whether stdout is a terminal, checked once. Spinners and bars are
+    /// hidden otherwise so piped output and CI logs stay clean.
+    fn interactive() -> bool {
+        static INTERACTIVE: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+        *INTERACTIVE.get_or_init(|| std::io::IsTerminal::is_terminal(&std::io::stdout()))
+    }
+
     pub fn show_spinner(message: &str) -> indicatif::ProgressBar {
+        if is_quiet() || !Self::interactive() {
+            return indicatif::ProgressBar::hidden();
+        }
         let pb = indicatif::ProgressBar::new_spinner();
         pb.set_style(
             indicatif::ProgressStyle::default_spinner()
@@ -196,6 +511,9 @@ impl ProgressIndicator {
     }
 
     pub fn show_progress(total: u64, message: &str) -> indicatif::ProgressBar {
+        if is_quiet() || !Self::interactive() {
+            return indicatif::ProgressBar::hidden();
+        }
         let pb = indicatif::ProgressBar::new(total);
         pb.set_style(
             indicatif::ProgressStyle::default_bar()
@@ -208,7 +526,41 @@ impl ProgressIndicator {
     }
 }
 
+/// Quotes an argument the way a POSIX shell would need it, when it has to be.
+pub(crate) fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+pub fn format_timestamp(timestamp: chrono::DateTime<chrono::Utc>, local: bool) -> String {
+    if local {
+        timestamp.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S %Z").to_string()
+    } else {
+        timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+    }
+}
+
+static QUIET: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Turns off decorative output (headers, success and info lines, spinners)
+/// for the rest of the run. Errors, warnings and requested data still print.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, std::sync::atomic::Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(std::sync::atomic::Ordering::Relaxed)
+}
+
 pub fn print_success(message: &str) {
+    if is_quiet() {
+        return;
+    }
     println!("{} {}", "✓".bright_green().bold(), message.bright_white());
 }
 
@@ -221,14 +573,44 @@ pub fn print_warning(message: &str) {
 }
 
 pub fn print_info(message: &str) {
+    if is_quiet() {
+        return;
+    }
     println!("{} {}", "ℹ".bright_blue().bold(), message.bright_white());
 }
 
+/// Asks a yes/no question on the terminal and returns the answer. Anything
+/// but `y`/`yes` declines. When stdin or stdout isn't a terminal there is no
+/// one to ask, so the answer is yes.
+pub fn confirm(question: &str) -> bool {
+    use std::io::{IsTerminal, Write};
+    if !std::io::stdout().is_terminal() || !std::io::stdin().is_terminal() {
+        return true;
+    }
+    print!("{} {} [y/N] ", "?".bright_yellow().bold(), question.bright_white());
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
 pub fn print_header(title: &str) {
+    if is_quiet() {
+        return;
+    }
     let len = title.len() + 4;
     let border = "═".repeat(len);
     
     println!("{}", border.bright_cyan());
     println!("  {}  ", title.bright_cyan().bold());
     println!("{}", border.bright_cyan());
-}
\ No newline at end of file
+}
+
+/// An empty line separating decorative output; skipped with `--quiet`.
+pub fn print_blank() {
+    if !is_quiet() {
+        println!();
+    }
+}
@@ -0,0 +1,71 @@
+use crate::{cli::ProcessConfig, pattern::wildcard_match, Result, RpmError};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+use tokio::time::Duration;
+
+/// Watches a process's configured paths and reports its name on `restart_tx`
+/// once a burst of file changes has been quiet for the debounce interval.
+pub struct FileWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl FileWatcher {
+    pub fn new(config: &ProcessConfig, restart_tx: mpsc::UnboundedSender<String>) -> Result<Self> {
+        let ignore = config.watch_ignore.clone();
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                if event.paths.iter().any(|path| !is_ignored(path, &ignore)) {
+                    let _ = event_tx.send(());
+                }
+            }
+        })
+        .map_err(|e| RpmError::Process(format!("Failed to create file watcher: {}", e)))?;
+
+        for path in &config.watch {
+            let path = resolve_watch_path(path, config.cwd.as_deref());
+            watcher.watch(&path, RecursiveMode::Recursive).map_err(|e| {
+                RpmError::Process(format!("Failed to watch '{}': {}", path.display(), e))
+            })?;
+        }
+
+        let name = config.name.clone();
+        let debounce = Duration::from_millis(config.watch_delay);
+        tokio::spawn(async move {
+            while event_rx.recv().await.is_some() {
+                // Keep absorbing events until the files have been quiet for the debounce window.
+                loop {
+                    match tokio::time::timeout(debounce, event_rx.recv()).await {
+                        Ok(Some(())) => continue,
+                        Ok(None) => return,
+                        Err(_) => break,
+                    }
+                }
+
+                tracing::info!("Detected file changes for process '{}'", name);
+                if restart_tx.send(name.clone()).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok(FileWatcher { _watcher: watcher })
+    }
+}
+
+fn resolve_watch_path(path: &str, cwd: Option<&str>) -> PathBuf {
+    let path = Path::new(path);
+    match cwd {
+        Some(cwd) if path.is_relative() => Path::new(cwd).join(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn is_ignored(path: &Path, ignore: &[String]) -> bool {
+    path.components().any(|component| {
+        let component = component.as_os_str().to_string_lossy();
+        ignore.iter().any(|pattern| wildcard_match(pattern, &component))
+    })
+}